@@ -73,9 +73,10 @@ fn benchmark_market_order(c: &mut Criterion) {
 
 fn benchmark_cancel_order(c: &mut Criterion) {
     let mut book = setup_book_with_depth(100, 10);
-    let (order_id, _) = book
+    let order_id = book
         .add_limit_order(OrderSide::Buy, dec!(99.98), dec!(1.0))
-        .expect("invalid order");
+        .expect("invalid order")
+        .order_id;
 
     c.bench_function("cancel_limit_order", |b| {
         b.iter(|| book.cancel_limit_order(black_box(order_id)))
@@ -0,0 +1,740 @@
+use crate::error::BookError;
+use crate::order::OwnerId;
+use crate::order_book::Orders;
+
+use rust_decimal::Decimal;
+
+/// How an incoming quantity is split across the resting orders of one
+/// price level.
+///
+/// The book calls `allocate` once per level it matches against, with the
+/// level's queue in time priority and the quantity the incoming order still
+/// wants at that price.
+///
+/// Implementations must return the quantity taken from each order in queue
+/// order. The vector may be shorter than the queue, missing entries take
+/// nothing. Every allocation must be between zero and the order's
+/// quantity, and the allocations must add up to exactly the smaller of
+/// `quantity` and the level's total volume.
+///
+/// Strategies must be `Send + Sync` so the book can move to a matching
+/// thread.
+pub trait AllocationStrategy: Send + Sync {
+    fn allocate(&self, level: &Orders, quantity: Decimal) -> Vec<Decimal>;
+}
+
+/// One stage of an [`AllocationPipeline`].
+///
+/// `allocations` holds one entry per resting order, in queue order, with
+/// what earlier stages have given each order. A step adds to it out of
+/// whatever is left of `quantity`, never giving an order more than its
+/// remaining size.
+pub trait AllocationStep: Send + Sync {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]);
+}
+
+// Quantity not yet allocated by earlier steps
+fn unallocated(quantity: Decimal, allocations: &[Decimal]) -> Decimal {
+    quantity - allocations.iter().sum::<Decimal>()
+}
+
+// Round down to a multiple of `lot_size`
+fn round_to_lot(quantity: Decimal, lot_size: Decimal) -> Decimal {
+    (quantity / lot_size).floor() * lot_size
+}
+
+// Give up to `budget` to orders in queue order, returning what was given
+fn fill_in_queue_order(level: &Orders, budget: Decimal, allocations: &mut [Decimal]) -> Decimal {
+    let mut remaining_budget = budget;
+    for (order, allocation) in level.orders().iter().zip(allocations.iter_mut()) {
+        if remaining_budget == Decimal::ZERO {
+            break;
+        }
+        let extra = remaining_budget.min(order.quantity - *allocation);
+        *allocation += extra;
+        remaining_budget -= extra;
+    }
+    budget - remaining_budget
+}
+
+/// Price-time priority: each order is filled completely before the next
+/// one is touched. The default for every book.
+///
+/// As a pipeline step it allocates everything that is left in time
+/// priority.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Fifo;
+
+impl AllocationStrategy for Fifo {
+    fn allocate(&self, level: &Orders, quantity: Decimal) -> Vec<Decimal> {
+        let mut remaining_quantity = quantity;
+        let mut allocations = Vec::new();
+
+        for order in level.orders() {
+            if remaining_quantity == Decimal::ZERO {
+                break;
+            }
+            let fill_quantity = remaining_quantity.min(order.quantity);
+            allocations.push(fill_quantity);
+            remaining_quantity -= fill_quantity;
+        }
+
+        allocations
+    }
+}
+
+impl AllocationStep for Fifo {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]) {
+        fill_in_queue_order(level, unallocated(quantity, allocations), allocations);
+    }
+}
+
+/// Pro-rata allocation: each order receives a share of the incoming
+/// quantity proportional to its size.
+///
+/// 1. Each order's share `quantity * order / level volume` is rounded down
+///    to a multiple of `lot_size`
+/// 2. Shares below `min_allocation` are dropped
+/// 3. Whatever is left after rounding is allocated FIFO, so the result is
+///    deterministic and the level always fills as much as it can
+///
+/// As a pipeline step only the first two stages run, over what earlier
+/// steps left of both the incoming quantity and each order, and the
+/// rounding remainder is passed on to the next step.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{OrderBook, OrderSide, ProRata};
+/// let mut book = OrderBook::new(dec!(0.01))
+///     .unwrap()
+///     .with_allocation(ProRata::new(dec!(1)).unwrap());
+/// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+/// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(30)).unwrap();
+///
+/// // 10 / 40 and 30 / 40 of 9 round down to 2 and 6, the last lot goes FIFO
+/// let fills = book.execute_market_order(OrderSide::Buy, dec!(9)).unwrap().fills;
+/// assert_eq!(fills[0].quantity, dec!(3));
+/// assert_eq!(fills[1].quantity, dec!(6));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ProRata {
+    lot_size: Decimal,
+    min_allocation: Decimal,
+}
+
+impl ProRata {
+    pub fn new(lot_size: Decimal) -> Result<Self, BookError> {
+        if lot_size <= Decimal::ZERO {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        Ok(Self {
+            lot_size,
+            min_allocation: Decimal::ZERO,
+        })
+    }
+
+    /// Drop pro-rata shares smaller than `min_allocation`, leaving them to
+    /// the FIFO remainder pass. The minimum must be positive.
+    pub fn with_min_allocation(mut self, min_allocation: Decimal) -> Result<Self, BookError> {
+        if min_allocation <= Decimal::ZERO {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        self.min_allocation = min_allocation;
+        Ok(self)
+    }
+}
+
+impl AllocationStrategy for ProRata {
+    fn allocate(&self, level: &Orders, quantity: Decimal) -> Vec<Decimal> {
+        let mut allocations = vec![Decimal::ZERO; level.orders().len()];
+        self.apply(level, quantity, &mut allocations);
+        Fifo.apply(level, quantity, &mut allocations);
+        allocations
+    }
+}
+
+impl AllocationStep for ProRata {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]) {
+        let remaining_quantity = unallocated(quantity, allocations);
+        let open_volume: Decimal = level
+            .orders()
+            .iter()
+            .zip(allocations.iter())
+            .map(|(order, allocation)| order.quantity - allocation)
+            .sum();
+
+        if remaining_quantity >= open_volume {
+            fill_in_queue_order(level, remaining_quantity, allocations);
+            return;
+        }
+
+        for (order, allocation) in level.orders().iter().zip(allocations.iter_mut()) {
+            let open_quantity = order.quantity - *allocation;
+            let share = round_to_lot(
+                remaining_quantity * open_quantity / open_volume,
+                self.lot_size,
+            );
+            if share >= self.min_allocation {
+                *allocation += share;
+            }
+        }
+    }
+}
+
+/// Gives the level's top order, the order that first bettered the market
+/// at this price, priority over everyone else.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct TopOrder {
+    max_quantity: Option<Decimal>,
+}
+
+impl TopOrder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap how much the top order can receive from this step
+    pub fn with_max_quantity(mut self, max_quantity: Decimal) -> Self {
+        self.max_quantity = Some(max_quantity);
+        self
+    }
+}
+
+impl AllocationStep for TopOrder {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]) {
+        let Some(top_order) = level.top_order() else {
+            return;
+        };
+        let Some(position) = level
+            .orders()
+            .iter()
+            .position(|order| order.id == top_order)
+        else {
+            return;
+        };
+
+        let open_quantity = level.orders()[position].quantity - allocations[position];
+        let mut extra = unallocated(quantity, allocations).min(open_quantity);
+        if let Some(max_quantity) = self.max_quantity {
+            extra = extra.min(max_quantity);
+        }
+        allocations[position] += extra;
+    }
+}
+
+/// Allocates a fixed share of what earlier steps left of the incoming
+/// quantity in time priority, rounded down to a multiple of `lot_size`.
+/// After a top order step the share is of the quantity left once the top
+/// order is served, as in CME's configurable allocation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct FifoPercentage {
+    percentage: Decimal,
+    lot_size: Decimal,
+}
+
+impl FifoPercentage {
+    /// `percentage` is a fraction of the quantity left, e.g. 0.4 for 40%
+    pub fn new(percentage: Decimal, lot_size: Decimal) -> Result<Self, BookError> {
+        if percentage <= Decimal::ZERO || percentage > Decimal::ONE {
+            return Err(BookError::InvalidPercentage);
+        }
+
+        if lot_size <= Decimal::ZERO {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        Ok(Self {
+            percentage,
+            lot_size,
+        })
+    }
+}
+
+impl AllocationStep for FifoPercentage {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]) {
+        let remaining_quantity = unallocated(quantity, allocations);
+        let budget = round_to_lot(remaining_quantity * self.percentage, self.lot_size);
+        fill_in_queue_order(level, budget, allocations);
+    }
+}
+
+/// Shares what is left one lot at a time, round robin in time priority,
+/// across every order that is not yet filled. Typically follows a pro-rata
+/// step to spread its rounding remainder evenly instead of FIFO.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Leveling {
+    lot_size: Decimal,
+}
+
+impl Leveling {
+    pub fn new(lot_size: Decimal) -> Result<Self, BookError> {
+        if lot_size <= Decimal::ZERO {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        Ok(Self { lot_size })
+    }
+}
+
+impl AllocationStep for Leveling {
+    fn apply(&self, level: &Orders, quantity: Decimal, allocations: &mut [Decimal]) {
+        let mut remaining_quantity = unallocated(quantity, allocations);
+
+        while remaining_quantity > Decimal::ZERO {
+            let mut allocated = false;
+            for (order, allocation) in level.orders().iter().zip(allocations.iter_mut()) {
+                let extra = remaining_quantity
+                    .min(self.lot_size)
+                    .min(order.quantity - *allocation);
+                if extra > Decimal::ZERO {
+                    *allocation += extra;
+                    remaining_quantity -= extra;
+                    allocated = true;
+                }
+            }
+
+            if !allocated {
+                break; // Every order is filled
+            }
+        }
+    }
+}
+
+/// A level allocation built from steps run in sequence, the way
+/// configurable CME-style algorithms are described: for example top order,
+/// then a FIFO percentage, then pro-rata, then leveling.
+///
+/// Each step allocates out of what earlier steps left. Anything still
+/// unallocated at the end goes FIFO, so a level always fills as much as it
+/// can.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{AllocationPipeline, OrderBook, OrderSide, ProRata, TopOrder};
+/// let pipeline = AllocationPipeline::new()
+///     .then(TopOrder::new())
+///     .then(ProRata::new(dec!(1)).unwrap());
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap().with_allocation(pipeline);
+///
+/// // The first order sets the best bid and becomes the top order
+/// let top = book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(5)).unwrap().order_id;
+/// book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20)).unwrap();
+/// book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(60)).unwrap();
+///
+/// // The top order fills first, the other 20 split 1:3
+/// let fills = book.execute_market_order(OrderSide::Sell, dec!(25)).unwrap().fills;
+/// assert_eq!((fills[0].maker_order_id, fills[0].quantity), (top, dec!(5)));
+/// assert_eq!(fills[1].quantity, dec!(5));
+/// assert_eq!(fills[2].quantity, dec!(15));
+/// ```
+#[derive(Default)]
+pub struct AllocationPipeline {
+    steps: Vec<Box<dyn AllocationStep>>,
+}
+
+impl AllocationPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a step to run after the ones already added
+    pub fn then(mut self, step: impl AllocationStep + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+}
+
+impl AllocationStrategy for AllocationPipeline {
+    fn allocate(&self, level: &Orders, quantity: Decimal) -> Vec<Decimal> {
+        let mut allocations = vec![Decimal::ZERO; level.orders().len()];
+        for step in &self.steps {
+            step.apply(level, quantity, &mut allocations);
+        }
+        Fifo.apply(level, quantity, &mut allocations);
+        allocations
+    }
+}
+
+/// Reserves a share of every trade for designated lead market makers,
+/// then allocates the rest with a base strategy.
+///
+/// At each level a lead quotes at, it is guaranteed `percentage` of the
+/// incoming quantity (rounded down to `lot_size`), taken from its orders in
+/// time priority and capped at what it has resting there. Leads are served
+/// in the order they were added. Whatever is left, including anything a
+/// lead could not take, goes through the base strategy against the
+/// remaining size of every order, the leads included.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{Fifo, LeadMarketMaker, OrderBook, OrderSide};
+/// let lmm = LeadMarketMaker::new(Fifo, dec!(1))
+///     .unwrap()
+///     .with_lead(7, dec!(0.4))
+///     .unwrap();
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap().with_allocation(lmm);
+///
+/// let first = book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(50)).unwrap().order_id;
+/// let lead = book.add_limit_order_for(7, OrderSide::Sell, dec!(100.00), dec!(50)).unwrap().order_id;
+///
+/// // The lead takes 40% of 20 despite arriving second, FIFO shares the rest
+/// let fills = book.execute_market_order(OrderSide::Buy, dec!(20)).unwrap().fills;
+/// assert_eq!((fills[0].maker_order_id, fills[0].quantity), (first, dec!(12)));
+/// assert_eq!((fills[1].maker_order_id, fills[1].quantity), (lead, dec!(8)));
+/// ```
+pub struct LeadMarketMaker {
+    base: Box<dyn AllocationStrategy>,
+    lot_size: Decimal,
+    leads: Vec<(OwnerId, Decimal)>, // Owner and guaranteed share, in priority order
+}
+
+impl LeadMarketMaker {
+    pub fn new(
+        base: impl AllocationStrategy + 'static,
+        lot_size: Decimal,
+    ) -> Result<Self, BookError> {
+        if lot_size <= Decimal::ZERO {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        Ok(Self {
+            base: Box::new(base),
+            lot_size,
+            leads: Vec::new(),
+        })
+    }
+
+    /// Guarantee `owner` a `percentage` (e.g. 0.4 for 40%) of the incoming
+    /// quantity at every level it quotes at
+    pub fn with_lead(mut self, owner: OwnerId, percentage: Decimal) -> Result<Self, BookError> {
+        if percentage <= Decimal::ZERO || percentage > Decimal::ONE {
+            return Err(BookError::InvalidPercentage);
+        }
+
+        self.leads.push((owner, percentage));
+        Ok(self)
+    }
+}
+
+impl AllocationStrategy for LeadMarketMaker {
+    fn allocate(&self, level: &Orders, quantity: Decimal) -> Vec<Decimal> {
+        let mut allocations = vec![Decimal::ZERO; level.orders().len()];
+
+        for (owner, percentage) in &self.leads {
+            let mut budget = round_to_lot(quantity * percentage, self.lot_size)
+                .min(unallocated(quantity, &allocations));
+            for (order, allocation) in level.orders().iter().zip(allocations.iter_mut()) {
+                if budget == Decimal::ZERO {
+                    break;
+                }
+                if order.owner != Some(*owner) {
+                    continue;
+                }
+                let extra = budget.min(order.quantity - *allocation);
+                *allocation += extra;
+                budget -= extra;
+            }
+        }
+
+        // The base strategy sees every order at its size after the leads
+        let mut remaining_level = Orders::new();
+        for (order, allocation) in level.orders().iter().zip(&allocations) {
+            let mut order = order.clone();
+            order.quantity -= *allocation;
+            remaining_level.add_order(order);
+        }
+        remaining_level.top_order = level.top_order;
+
+        let base_allocations = self
+            .base
+            .allocate(&remaining_level, unallocated(quantity, &allocations));
+        for (allocation, base_allocation) in allocations.iter_mut().zip(base_allocations) {
+            *allocation += base_allocation;
+        }
+
+        allocations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{Order, OrderSide, OrderType};
+    use rust_decimal_macros::dec;
+
+    // A level whose first order is its top order
+    fn level(quantities: &[Decimal]) -> Orders {
+        let mut level = Orders::new();
+        for (id, quantity) in quantities.iter().enumerate() {
+            level.add_order(
+                Order::new(id as u64, *quantity, OrderType::Limit, OrderSide::Sell)
+                    .expect("valid order"),
+            );
+        }
+        level.top_order = Some(0);
+        level
+    }
+
+    fn assert_send<T: Send + Sync>() {}
+
+    #[test]
+    fn test_strategies_are_send() {
+        // The book holds its strategy boxed and must stay Send
+        assert_send::<Box<dyn AllocationStrategy>>();
+        assert_send::<AllocationPipeline>();
+    }
+
+    #[test]
+    fn test_fifo_allocation() {
+        let orders = level(&[dec!(5), dec!(10), dec!(5)]);
+        assert_eq!(Fifo.allocate(&orders, dec!(12)), vec![dec!(5), dec!(7)]);
+        assert_eq!(
+            Fifo.allocate(&orders, dec!(30)),
+            vec![dec!(5), dec!(10), dec!(5)]
+        );
+    }
+
+    #[test]
+    fn test_pro_rata_allocation() {
+        let pro_rata = ProRata::new(dec!(1)).expect("valid lot size");
+        let orders = level(&[dec!(50), dec!(30), dec!(20)]);
+
+        // Exact proportional split
+        assert_eq!(
+            pro_rata.allocate(&orders, dec!(10)),
+            vec![dec!(5), dec!(3), dec!(2)]
+        );
+
+        // 7 splits 3.5 / 2.1 / 1.4 -> 3 / 2 / 1, the last lot goes FIFO
+        assert_eq!(
+            pro_rata.allocate(&orders, dec!(7)),
+            vec![dec!(4), dec!(2), dec!(1)]
+        );
+
+        // Shares below the minimum are redistributed FIFO
+        let with_minimum = pro_rata
+            .with_min_allocation(dec!(2))
+            .expect("valid minimum");
+        assert_eq!(
+            with_minimum.allocate(&orders, dec!(7)),
+            vec![dec!(5), dec!(2), dec!(0)]
+        );
+
+        // Enough quantity fills everyone
+        assert_eq!(
+            pro_rata.allocate(&orders, dec!(150)),
+            vec![dec!(50), dec!(30), dec!(20)]
+        );
+
+        // Fractional lots
+        let fractional = ProRata::new(dec!(0.5)).expect("valid lot size");
+        assert_eq!(
+            fractional.allocate(&orders, dec!(3)),
+            vec![dec!(2.0), dec!(0.5), dec!(0.5)]
+        );
+
+        assert_eq!(ProRata::new(dec!(0)), Err(BookError::InvalidLotSize));
+        assert_eq!(
+            pro_rata.with_min_allocation(dec!(0)),
+            Err(BookError::InvalidQuantity)
+        );
+        assert_eq!(
+            pro_rata.with_min_allocation(dec!(-1)),
+            Err(BookError::InvalidQuantity)
+        );
+    }
+
+    // The scenarios below follow the allocation rules CME Globex publishes
+    // in its Client Systems Wiki, "Matching Algorithm Overview": algorithm
+    // A (top order, then pro-rata rounded down with a 2 lot minimum, then
+    // FIFO), algorithm K (top order, FIFO percentage of what the top order
+    // left, pro-rata, leveling, FIFO) and a lead market maker split ahead
+    // of pro-rata. Each vector is the exact allocation those rules give.
+    fn cme_pipeline() -> AllocationPipeline {
+        AllocationPipeline::new()
+            .then(TopOrder::new())
+            .then(FifoPercentage::new(dec!(0.4), dec!(1)).expect("valid percentage"))
+            .then(
+                ProRata::new(dec!(1))
+                    .expect("valid lot size")
+                    .with_min_allocation(dec!(2))
+                    .expect("valid minimum"),
+            )
+            .then(Leveling::new(dec!(1)).expect("valid lot size"))
+    }
+
+    #[test]
+    fn test_cme_fifo_percentage_with_top_order() {
+        let pipeline = cme_pipeline();
+        let orders = level(&[dec!(10), dec!(50), dec!(30), dec!(10)]);
+
+        // Incoming 40:
+        // - top order A takes all of its 10, leaving 30
+        // - FIFO 40% of the 30 left = 12 goes to B, leaving 18
+        // - pro-rata of 18 over open 38 / 30 / 10 gives 8.77 / 6.92 / 2.31,
+        //   rounded 8 / 6 / 2, all at or above the 2 lot minimum
+        // - leveling hands the last 2 out one lot each to B and C
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(40)),
+            vec![dec!(10), dec!(21), dec!(7), dec!(2)]
+        );
+
+        // Incoming 20: A takes 10, 40% of 10 = 4 to B, pro-rata of 6 over
+        // 46 / 30 / 10 gives 3 / 2 / 0, and leveling gives the last lot to B
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(20)),
+            vec![dec!(10), dec!(8), dec!(2), dec!(0)]
+        );
+
+        // Enough to fill everyone
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(200)),
+            vec![dec!(10), dec!(50), dec!(30), dec!(10)]
+        );
+    }
+
+    #[test]
+    fn test_cme_pro_rata_with_minimum() {
+        let pipeline = AllocationPipeline::new().then(TopOrder::new()).then(
+            ProRata::new(dec!(1))
+                .expect("valid lot size")
+                .with_min_allocation(dec!(2))
+                .expect("valid minimum"),
+        );
+        let orders = level(&[dec!(10), dec!(50), dec!(30), dec!(10)]);
+
+        // Incoming 40: A takes 10, 30 over 50 / 30 / 10 gives 16.67 / 10 /
+        // 3.33, rounded 16 / 10 / 3, and the last lot goes FIFO to B
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(40)),
+            vec![dec!(10), dec!(17), dec!(10), dec!(3)]
+        );
+
+        // Incoming 15: A takes 10, 5 over 50 / 30 / 10 gives 2.78 / 1.67 /
+        // 0.56, rounded 2 / 1 / 0. C's single lot is below the minimum, so
+        // the 3 left go FIFO to B.
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(15)),
+            vec![dec!(10), dec!(5), dec!(0), dec!(0)]
+        );
+    }
+
+    #[test]
+    fn test_cme_lead_market_maker_split() {
+        let mut orders = level(&[dec!(30), dec!(20), dec!(50)]);
+        orders.orders[1].owner = Some(7);
+        let lmm = LeadMarketMaker::new(ProRata::new(dec!(1)).expect("valid lot size"), dec!(1))
+            .expect("valid lot size")
+            .with_lead(7, dec!(0.4))
+            .expect("valid percentage");
+
+        // Incoming 50: the lead takes 40% = 20, all it has resting. The
+        // other 30 go pro-rata over 30 / 0 / 50, 11.25 / 0 / 18.75 rounded
+        // 11 / 0 / 18, and the last lot goes FIFO to A.
+        assert_eq!(
+            lmm.allocate(&orders, dec!(50)),
+            vec![dec!(12), dec!(20), dec!(18)]
+        );
+
+        // Incoming 10: the lead takes 4, 6 over 30 / 16 / 50 gives 1.88 /
+        // 1 / 3.13, rounded 1 / 1 / 3, and the last lot goes FIFO to A
+        assert_eq!(
+            lmm.allocate(&orders, dec!(10)),
+            vec![dec!(2), dec!(5), dec!(3)]
+        );
+    }
+
+    #[test]
+    fn test_top_order_with_pro_rata() {
+        // Top order capped at 5, then pro-rata with FIFO for the remainder
+        let pipeline = AllocationPipeline::new()
+            .then(TopOrder::new().with_max_quantity(dec!(5)))
+            .then(ProRata::new(dec!(1)).expect("valid lot size"));
+        let orders = level(&[dec!(20), dec!(40), dec!(40)]);
+
+        // Incoming 25: A takes 5, then 20 over open 15 / 40 / 40 gives
+        // 3.16 / 8.42 / 8.42, rounded 3 / 8 / 8, and the last lot goes to A
+        assert_eq!(
+            pipeline.allocate(&orders, dec!(25)),
+            vec![dec!(9), dec!(8), dec!(8)]
+        );
+
+        // Without a top order the step does nothing
+        let mut no_top = level(&[dec!(20), dec!(40), dec!(40)]);
+        no_top.top_order = None;
+        assert_eq!(
+            pipeline.allocate(&no_top, dec!(25)),
+            vec![dec!(5), dec!(10), dec!(10)]
+        );
+    }
+
+    #[test]
+    fn test_lead_market_maker() {
+        let mut orders = level(&[dec!(30), dec!(20), dec!(30), dec!(20)]);
+        orders.orders[1].owner = Some(7);
+        orders.orders[3].owner = Some(7);
+        orders.orders[2].owner = Some(9);
+
+        // Lead 7 is guaranteed 30% of 20 = 6 ahead of FIFO
+        let lmm = LeadMarketMaker::new(Fifo, dec!(1))
+            .expect("valid lot size")
+            .with_lead(7, dec!(0.3))
+            .expect("valid percentage");
+        assert_eq!(
+            lmm.allocate(&orders, dec!(20)),
+            vec![dec!(14), dec!(6), dec!(0), dec!(0)]
+        );
+
+        // The lead's guarantee spills over to its next order, and it keeps
+        // its place in the base allocation for what is left
+        assert_eq!(
+            lmm.allocate(&orders, dec!(90)),
+            vec![dec!(30), dec!(20), dec!(30), dec!(10)]
+        );
+
+        // Two leads over a pro-rata base: 7 takes 20% of 50 = 10, 9 takes
+        // 10% = 5, and 35 is shared pro-rata over 30 / 10 / 25 / 20
+        let lmm = LeadMarketMaker::new(ProRata::new(dec!(1)).expect("valid lot size"), dec!(1))
+            .expect("valid lot size")
+            .with_lead(7, dec!(0.2))
+            .expect("valid percentage")
+            .with_lead(9, dec!(0.1))
+            .expect("valid percentage");
+        // 12.35 / 4.12 / 10.29 / 8.24 round to 12 / 4 / 10 / 8, the last lot goes FIFO
+        assert_eq!(
+            lmm.allocate(&orders, dec!(50)),
+            vec![dec!(13), dec!(14), dec!(15), dec!(8)]
+        );
+
+        // A level without the lead falls back to the base strategy
+        let anonymous = level(&[dec!(10), dec!(10)]);
+        assert_eq!(
+            LeadMarketMaker::new(Fifo, dec!(1))
+                .expect("valid lot size")
+                .with_lead(7, dec!(0.5))
+                .expect("valid percentage")
+                .allocate(&anonymous, dec!(15)),
+            vec![dec!(10), dec!(5)]
+        );
+    }
+
+    #[test]
+    fn test_leveling_round_robin() {
+        let leveling = AllocationPipeline::new().then(Leveling::new(dec!(1)).expect("valid"));
+        let orders = level(&[dec!(1), dec!(5), dec!(5)]);
+        assert_eq!(
+            leveling.allocate(&orders, dec!(6)),
+            vec![dec!(1), dec!(3), dec!(2)]
+        );
+
+        assert_eq!(
+            FifoPercentage::new(dec!(1.5), dec!(1)),
+            Err(BookError::InvalidPercentage)
+        );
+    }
+}
@@ -0,0 +1,739 @@
+use crate::error::BookError;
+use crate::fees::FeeCurrency;
+use crate::order::{Fill, OrderId, OrderSide};
+use crate::order_book::{MatchBudget, OrderBook, Orders};
+use crate::phase::TradingPhase;
+use crate::ticks::Tick;
+
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+// Taker of the one-sided plans an uncross pairs up. Order ids count up from
+// zero and never reach it, so the plans can't be mistaken for a real order.
+const UNCROSS_TAKER: OrderId = OrderId::MAX;
+
+/// The price an auction would clear at if it were uncrossed now.
+///
+/// # Fields
+/// * `price` - Clearing price
+/// * `matched_volume` - Quantity that trades at the clearing price
+/// * `imbalance` - Quantity left unmatched at the clearing price on the heavier side
+/// * `imbalance_side` - The heavier side, None when both sides match exactly
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AuctionEquilibrium {
+    pub price: Decimal,
+    pub matched_volume: Decimal,
+    pub imbalance: Decimal,
+    pub imbalance_side: Option<OrderSide>,
+}
+
+/// The outcome of uncrossing an auction.
+///
+/// # Fields
+/// * `equilibrium` - Where the auction cleared, None if the book was not crossed
+/// * `fills` - Every fill, all at the clearing price
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct UncrossResult {
+    pub equilibrium: Option<AuctionEquilibrium>,
+    pub fills: Vec<Fill>,
+}
+
+impl OrderBook {
+    /// Start a call auction, shorthand for
+    /// `transition_to(TradingPhase::Auction)`.
+    ///
+    /// Until `uncross` is called limit orders rest without matching, even
+    /// when they cross the book, and cancels work as usual. Market orders
+    /// have no price to take part in the auction at and are rejected.
+    pub fn start_auction(&mut self) -> Result<(), BookError> {
+        self.transition_to(TradingPhase::Auction)
+    }
+
+    /// Price used to break ties between otherwise equal clearing prices.
+    /// This is the last trade price unless set with `set_reference_price`.
+    pub fn reference_price(&self) -> Option<Decimal> {
+        self.reference_price
+    }
+
+    /// Set the reference price, e.g. the previous close before an opening
+    /// auction. The next trade replaces it.
+    pub fn set_reference_price(&mut self, price: Decimal) -> Result<(), BookError> {
+        if price <= Decimal::ZERO {
+            return Err(BookError::InvalidPrice);
+        }
+
+        self.reference_price = Some(price);
+        self.publish_indicative();
+        Ok(())
+    }
+
+    /// The indicative equilibrium published during an auction or pre-open: the price,
+    /// matched volume and imbalance the auction would clear at if it were
+    /// uncrossed now. None in other phases or while the book is not
+    /// crossed.
+    ///
+    /// Kept up to date after every order event from running cumulative bid
+    /// and ask volumes by tick over the crossed range, which each event
+    /// adjusts instead of summing the levels again, so reading it is free.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.start_auction().unwrap();
+    /// book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10)).unwrap();
+    /// assert_eq!(book.indicative_equilibrium(), None);
+    ///
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(4)).unwrap();
+    /// let indicative = book.indicative_equilibrium().unwrap();
+    /// assert_eq!(indicative.price, dec!(101.00));
+    /// assert_eq!(indicative.matched_volume, dec!(4));
+    /// assert_eq!(indicative.imbalance, dec!(6));
+    /// assert_eq!(indicative.imbalance_side, Some(OrderSide::Buy));
+    /// ```
+    pub fn indicative_equilibrium(&self) -> Option<AuctionEquilibrium> {
+        self.indicative
+    }
+
+    /// The price the auction would clear at if uncrossed now, or None if
+    /// nothing would trade.
+    ///
+    /// Only prices in the crossed range between the best ask and the best
+    /// bid can trade, so only the levels in that range are visited. Every
+    /// resting price in it is a candidate. The clearing price is the
+    /// candidate that
+    /// 1. maximizes the executable volume, then
+    /// 2. minimizes the imbalance left at that price, then
+    /// 3. follows market pressure: the highest candidate if every one
+    ///    leaves buyers unfilled, the lowest if every one leaves sellers
+    ///    unfilled, then
+    /// 4. is closest to the reference price (the middle of the remaining
+    ///    candidates without one), the lower price on an exact tie.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.start_auction().unwrap();
+    /// book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(99.00), dec!(25)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(20)).unwrap();
+    ///
+    /// let equilibrium = book.auction_equilibrium().unwrap();
+    /// assert_eq!(equilibrium.price, dec!(100.00));
+    /// assert_eq!(equilibrium.matched_volume, dec!(30));
+    /// assert_eq!(equilibrium.imbalance, dec!(15));
+    /// assert_eq!(equilibrium.imbalance_side, Some(OrderSide::Sell));
+    /// ```
+    pub fn auction_equilibrium(&self) -> Option<AuctionEquilibrium> {
+        AuctionDepth::new(&self.bids, &self.asks).equilibrium(self.reference_price)
+    }
+
+    /// End the auction: execute everything that crosses at the clearing
+    /// price from `auction_equilibrium` and switch to continuous trading.
+    ///
+    /// Each side is allocated in price priority, and within the marginal
+    /// level by the book's allocation strategy, as if a single order for
+    /// the matched volume had swept it. Buy and sell allocations are then
+    /// paired off in priority order. Every fill is at the clearing price,
+    /// and the order that arrived later is reported as the taker.
+    ///
+    /// The clearing price becomes the reference price.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide, TradingPhase};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.start_auction().unwrap();
+    /// let buy = book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10)).unwrap();
+    /// let sell = book.add_limit_order(OrderSide::Sell, dec!(99.00), dec!(4)).unwrap();
+    /// assert!(buy.fills.is_empty() && sell.fills.is_empty());
+    ///
+    /// let result = book.uncross().unwrap();
+    /// assert_eq!(result.fills.len(), 1);
+    /// assert_eq!(result.fills[0].price, dec!(101.00)); // Buyers outweigh sellers
+    /// assert_eq!(result.fills[0].taker_order_id, sell.order_id);
+    /// assert_eq!(book.best_bid_volume(), Some(dec!(6)));
+    /// assert_eq!(book.phase(), TradingPhase::Continuous);
+    /// ```
+    pub fn uncross(&mut self) -> Result<UncrossResult, BookError> {
+        if self.phase != TradingPhase::Auction {
+            return Err(BookError::NotAllowedInPhase(self.phase));
+        }
+
+        let result = self.execute_uncross();
+        self.set_phase(TradingPhase::Continuous);
+        self.trigger_stops();
+        Ok(result)
+    }
+
+    // Execute everything that crosses at the clearing price, leaving the
+    // phase alone
+    pub(crate) fn execute_uncross(&mut self) -> UncrossResult {
+        let equilibrium = self.auction_equilibrium();
+        let mut fills = Vec::new();
+
+        if let Some(equilibrium) = &equilibrium {
+            let budget = MatchBudget::Quantity(equilibrium.matched_volume);
+            // Each plan fills one side as makers against an anonymous
+            // taker, the fills paired up from them are the actual trades
+            let price = Some(equilibrium.price);
+            let bid_plan = self.plan_match(UNCROSS_TAKER, None, OrderSide::Sell, price, budget);
+            let ask_plan = self.plan_match(UNCROSS_TAKER, None, OrderSide::Buy, price, budget);
+
+            // Pair the buy allocations with the sell allocations
+            let mut sells = ask_plan
+                .fills
+                .iter()
+                .map(|fill| (fill.maker_order_id, fill.maker_owner, fill.quantity));
+            let mut sell = sells.next();
+            for bid in &bid_plan.fills {
+                let mut buy_quantity = bid.quantity;
+                while buy_quantity > Decimal::ZERO {
+                    let Some((sell_id, sell_owner, sell_quantity)) = sell.as_mut() else {
+                        break;
+                    };
+                    let quantity = buy_quantity.min(*sell_quantity);
+
+                    // The later order takes
+                    let buy = (bid.maker_order_id, bid.maker_owner);
+                    let sell_order = (*sell_id, *sell_owner);
+                    let (taker_side, taker, maker) = if buy.0 > sell_order.0 {
+                        (OrderSide::Buy, buy, sell_order)
+                    } else {
+                        (OrderSide::Sell, sell_order, buy)
+                    };
+                    let mut fill = Fill {
+                        quantity,
+                        price: equilibrium.price,
+                        taker_order_id: taker.0,
+                        maker_order_id: maker.0,
+                        taker_side,
+                        taker_owner: taker.1,
+                        maker_owner: maker.1,
+                        taker_fee: Decimal::ZERO,
+                        maker_fee: Decimal::ZERO,
+                        fee_currency: FeeCurrency::default(),
+                    };
+                    self.charge_fees(&mut fill);
+                    fills.push(fill);
+
+                    buy_quantity -= quantity;
+                    *sell_quantity -= quantity;
+                    if *sell_quantity == Decimal::ZERO {
+                        sell = sells.next();
+                    }
+                }
+            }
+
+            // The plans fill at each resting price, the trades themselves
+            // all happen at the clearing price
+            self.remove_matched(OrderSide::Sell, &bid_plan);
+            self.remove_matched(OrderSide::Buy, &ask_plan);
+            self.record_fills(&fills);
+        }
+
+        self.refresh_indicative();
+        UncrossResult { equilibrium, fills }
+    }
+
+    // Update the running volumes and the indicative equilibrium after
+    // `delta` was added to (or with a negative delta, removed from) the
+    // orders on `side` at `tick` while orders accumulate
+    pub(crate) fn on_auction_event(&mut self, side: OrderSide, tick: &Tick, delta: Decimal) {
+        let Some(depth) = &mut self.auction_depth else {
+            return;
+        };
+
+        depth.update(&self.bids, &self.asks, side, tick, delta);
+        self.indicative = depth.equilibrium(self.reference_price);
+    }
+
+    // Rebuild the running volumes from the book, after anything that
+    // changes many levels at once (phase changes, uncrosses, tick changes)
+    pub(crate) fn refresh_indicative(&mut self) {
+        self.auction_depth = self
+            .phase
+            .accumulates_orders()
+            .then(|| AuctionDepth::new(&self.bids, &self.asks));
+        self.publish_indicative();
+    }
+
+    // Derive the indicative equilibrium from the running volumes
+    pub(crate) fn publish_indicative(&mut self) {
+        self.indicative = self
+            .auction_depth
+            .as_ref()
+            .and_then(|depth| depth.equilibrium(self.reference_price));
+    }
+}
+
+// Cumulative bid and ask volumes by tick over the crossed range of a book,
+// kept up to date order by order while an auction collects orders.
+//
+// Every resting price from the best ask up to the best bid is a candidate
+// clearing price and has a point on the curve, holding the bid volume at
+// or above it and the ask volume at or below it. An order event changes
+// those totals for the points on one side of its price, and adds or drops
+// the points the crossed range gains or loses, so no level outside the
+// range is ever visited and no total is summed again.
+#[derive(Clone, Default)]
+pub(crate) struct AuctionDepth {
+    curve: BTreeMap<Tick, (Decimal, Decimal)>, // (bids at or above, asks at or below)
+}
+
+// The best ask and best bid, if the book is crossed
+fn crossed_range(
+    bids: &BTreeMap<Tick, Orders>,
+    asks: &BTreeMap<Tick, Orders>,
+) -> Option<(Tick, Tick)> {
+    let (best_bid, _) = bids.last_key_value()?;
+    let (best_ask, _) = asks.first_key_value()?;
+    (best_bid >= best_ask).then(|| (best_ask.clone(), best_bid.clone()))
+}
+
+impl AuctionDepth {
+    // The curve over the book's current crossed range
+    pub(crate) fn new(bids: &BTreeMap<Tick, Orders>, asks: &BTreeMap<Tick, Orders>) -> Self {
+        let mut depth = Self::default();
+        if let Some((low, high)) = crossed_range(bids, asks) {
+            depth.insert_points(bids, asks, low..=high);
+        }
+        depth
+    }
+
+    // `delta` was added to the bids or asks at `tick`, and the book now
+    // holds `bids` and `asks`
+    fn update(
+        &mut self,
+        bids: &BTreeMap<Tick, Orders>,
+        asks: &BTreeMap<Tick, Orders>,
+        side: OrderSide,
+        tick: &Tick,
+        delta: Decimal,
+    ) {
+        // A bid counts at every price up to its own, an ask at every price
+        // from its own
+        match side {
+            OrderSide::Buy => {
+                for (bid_volume, _) in self.curve.range_mut(..=tick).map(|(_, point)| point) {
+                    *bid_volume += delta;
+                }
+            }
+            OrderSide::Sell => {
+                for (_, ask_volume) in self.curve.range_mut(tick..).map(|(_, point)| point) {
+                    *ask_volume += delta;
+                }
+            }
+        }
+
+        let Some((low, high)) = crossed_range(bids, asks) else {
+            self.curve.clear();
+            return;
+        };
+
+        // Drop the points that left the range or no longer rest
+        while let Some(first) = self.curve.first_entry() {
+            if *first.key() >= low {
+                break;
+            }
+            first.remove();
+        }
+        while let Some(last) = self.curve.last_entry() {
+            if *last.key() <= high {
+                break;
+            }
+            last.remove();
+        }
+        if !bids.contains_key(tick) && !asks.contains_key(tick) {
+            self.curve.remove(tick);
+        }
+
+        // Add the points the range gained, a new level inside it or levels
+        // beyond either end
+        let (Some((first, _)), Some((last, _))) =
+            (self.curve.first_key_value(), self.curve.last_key_value())
+        else {
+            self.insert_points(bids, asks, low..=high);
+            return;
+        };
+        let (first, last) = (first.clone(), last.clone());
+        if first < *tick && *tick < last && !self.curve.contains_key(tick) {
+            self.insert_points(bids, asks, tick.clone()..=tick.clone());
+        }
+        if low < first {
+            self.insert_points(bids, asks, (Bound::Included(low), Bound::Excluded(first)));
+        }
+        if high > last {
+            self.insert_points(bids, asks, (Bound::Excluded(last), Bound::Included(high)));
+        }
+    }
+
+    // Add a point for every resting price in `range`, which must have no
+    // points yet and no points between it and its nearest neighbours
+    fn insert_points(
+        &mut self,
+        bids: &BTreeMap<Tick, Orders>,
+        asks: &BTreeMap<Tick, Orders>,
+        range: impl RangeBounds<Tick> + Clone,
+    ) {
+        let mut ticks: Vec<&Tick> = bids
+            .range(range.clone())
+            .chain(asks.range(range.clone()))
+            .map(|(tick, _)| tick)
+            .collect();
+        ticks.sort_unstable();
+        ticks.dedup();
+        let (Some(&lowest), Some(&highest)) = (ticks.first(), ticks.last()) else {
+            return;
+        };
+
+        // Continue from the neighbouring points' totals
+        let mut ask_volume = self
+            .curve
+            .range(..lowest)
+            .next_back()
+            .map_or(Decimal::ZERO, |(_, (_, asks))| *asks);
+        let mut bid_volume = self
+            .curve
+            .range((Bound::Excluded(highest), Bound::Unbounded))
+            .next()
+            .map_or(Decimal::ZERO, |(_, (bids, _))| *bids);
+        let volume = |levels: &BTreeMap<Tick, Orders>, tick: &Tick| {
+            levels
+                .get(tick)
+                .map_or(Decimal::ZERO, |orders| orders.total_volume)
+        };
+
+        let mut points = Vec::with_capacity(ticks.len());
+        for tick in &ticks {
+            ask_volume += volume(asks, tick);
+            points.push(ask_volume);
+        }
+        for (tick, ask_volume) in ticks.iter().zip(points).rev() {
+            bid_volume += volume(bids, tick);
+            self.curve.insert((*tick).clone(), (bid_volume, ask_volume));
+        }
+    }
+
+    // The clearing price over the curve, see `OrderBook::auction_equilibrium`
+    fn equilibrium(&self, reference_price: Option<Decimal>) -> Option<AuctionEquilibrium> {
+        let mut candidates: Vec<_> = self // (price, matched volume, bid surplus)
+            .curve
+            .iter()
+            .map(|(tick, (bid_volume, ask_volume))| {
+                (
+                    tick.level(),
+                    (*bid_volume).min(*ask_volume),
+                    bid_volume - ask_volume,
+                )
+            })
+            .filter(|(_, matched_volume, _)| *matched_volume > Decimal::ZERO)
+            .collect();
+
+        let max_volume = candidates.iter().map(|(_, volume, _)| *volume).max()?;
+        candidates.retain(|(_, volume, _)| *volume == max_volume);
+        let min_imbalance = candidates
+            .iter()
+            .map(|(_, _, surplus)| surplus.abs())
+            .min()?;
+        candidates.retain(|(_, _, surplus)| surplus.abs() == min_imbalance);
+
+        let (lowest, highest) = (candidates[0], candidates[candidates.len() - 1]);
+        let (price, matched_volume, surplus) = if candidates
+            .iter()
+            .all(|(_, _, surplus)| *surplus > Decimal::ZERO)
+        {
+            highest
+        } else if candidates
+            .iter()
+            .all(|(_, _, surplus)| *surplus < Decimal::ZERO)
+        {
+            lowest
+        } else {
+            let target = reference_price.unwrap_or((lowest.0 + highest.0) / Decimal::TWO);
+            *candidates
+                .iter()
+                .min_by_key(|(price, _, _)| (*price - target).abs())?
+        };
+
+        let imbalance_side = if surplus > Decimal::ZERO {
+            Some(OrderSide::Buy)
+        } else if surplus < Decimal::ZERO {
+            Some(OrderSide::Sell)
+        } else {
+            None
+        };
+
+        Some(AuctionEquilibrium {
+            price,
+            matched_volume,
+            imbalance: surplus.abs(),
+            imbalance_side,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn auction_book() -> OrderBook {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.start_auction().expect("book starts continuous");
+        book
+    }
+
+    #[test]
+    fn test_auction_accumulates_and_uncrosses() {
+        let mut book = auction_book();
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order"); // 0
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20))
+            .expect("invalid order"); // 1
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(30))
+            .expect("invalid order"); // 2
+        book.add_limit_order(OrderSide::Sell, dec!(98.00), dec!(15))
+            .expect("invalid order"); // 3
+        book.add_limit_order(OrderSide::Sell, dec!(99.00), dec!(10))
+            .expect("invalid order"); // 4
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(20))
+            .expect("invalid order"); // 5
+        book.add_limit_order(OrderSide::Sell, dec!(102.00), dec!(5))
+            .expect("invalid order"); // 6
+
+        // Nothing matched, the book is crossed
+        assert_eq!(book.best_bid(), Some(dec!(101.00)));
+        assert_eq!(book.best_ask(), Some(dec!(98.00)));
+
+        // Market orders can't take part, cancels still work
+        assert_eq!(
+            book.execute_market_order(OrderSide::Buy, dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Auction))
+        );
+        let cancelled = book
+            .add_limit_order(OrderSide::Sell, dec!(97.00), dec!(1))
+            .expect("invalid order");
+        book.cancel_limit_order(cancelled.order_id)
+            .expect("order rests");
+
+        // Executable volume is 15 / 25 / 30 / 10 at 98 / 99 / 100 / 101
+        let result = book.uncross().expect("book is in an auction");
+        let equilibrium = result.equilibrium.expect("book was crossed");
+        assert_eq!(equilibrium.price, dec!(100.00));
+        assert_eq!(equilibrium.matched_volume, dec!(30));
+        assert_eq!(equilibrium.imbalance, dec!(15));
+        assert_eq!(equilibrium.imbalance_side, Some(OrderSide::Sell));
+
+        let fills: Vec<(OrderId, OrderId, Decimal)> = result
+            .fills
+            .iter()
+            .map(|fill| (fill.maker_order_id, fill.taker_order_id, fill.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (0, 3, dec!(10)),
+                (1, 3, dec!(5)),
+                (1, 4, dec!(10)),
+                (1, 5, dec!(5)),
+            ]
+        );
+        assert!(result.fills.iter().all(|fill| fill.price == dec!(100.00)));
+
+        // Continuous trading resumes on the uncrossed book
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+        assert_eq!(book.reference_price(), Some(dec!(100.00)));
+        assert_eq!(book.bid_levels(5), vec![(dec!(99.00), dec!(30))]);
+        assert_eq!(
+            book.ask_levels(5),
+            vec![(dec!(100.00), dec!(15)), (dec!(102.00), dec!(5))]
+        );
+        assert_eq!(book.total_bid_volume, dec!(30));
+        assert_eq!(book.total_ask_volume, dec!(20));
+        let report = book
+            .execute_market_order(OrderSide::Buy, dec!(15))
+            .expect("continuous again");
+        assert_eq!(report.fills[0].maker_order_id, 5);
+    }
+
+    #[test]
+    fn test_equilibrium_tie_breaks() {
+        // Equal volume at 100 and 101, 100 leaves no imbalance
+        let mut book = auction_book();
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(5))
+            .expect("invalid order");
+        assert_eq!(
+            book.auction_equilibrium().map(|e| e.price),
+            Some(dec!(100.00))
+        );
+
+        // Buy pressure clears at the highest candidate, sell pressure at the lowest
+        let mut book = auction_book();
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(20))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        let equilibrium = book.auction_equilibrium().expect("book is crossed");
+        assert_eq!(equilibrium.price, dec!(101.00));
+        assert_eq!(equilibrium.imbalance_side, Some(OrderSide::Buy));
+
+        let mut book = auction_book();
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(20))
+            .expect("invalid order");
+        assert_eq!(
+            book.auction_equilibrium().map(|e| e.price),
+            Some(dec!(100.00))
+        );
+
+        // Balanced at both prices, the reference price decides
+        let mut book = auction_book();
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        assert_eq!(
+            book.auction_equilibrium().map(|e| e.price),
+            Some(dec!(100.00)) // Lower price when the midpoint ties
+        );
+        book.set_reference_price(dec!(100.80)).expect("valid price");
+        assert_eq!(
+            book.auction_equilibrium().map(|e| e.price),
+            Some(dec!(101.00))
+        );
+        book.set_reference_price(dec!(100.20)).expect("valid price");
+        assert_eq!(
+            book.auction_equilibrium().map(|e| e.price),
+            Some(dec!(100.00))
+        );
+    }
+
+    #[test]
+    fn test_indicative_equilibrium_follows_order_events() {
+        let mut book = auction_book();
+        let check = |book: &OrderBook| {
+            assert_eq!(book.indicative_equilibrium(), book.auction_equilibrium());
+        };
+
+        let events = [
+            (OrderSide::Buy, dec!(100.00), dec!(10)),
+            (OrderSide::Sell, dec!(101.00), dec!(10)), // Not crossed yet
+            (OrderSide::Sell, dec!(99.00), dec!(5)),
+            (OrderSide::Buy, dec!(95.00), dec!(50)), // Below the crossed range
+            (OrderSide::Sell, dec!(105.00), dec!(50)), // Above the crossed range
+            (OrderSide::Buy, dec!(102.00), dec!(8)),
+            (OrderSide::Sell, dec!(100.00), dec!(7)),
+        ];
+        let mut ids = Vec::new();
+        for (side, price, quantity) in events {
+            let report = book
+                .add_limit_order(side, price, quantity)
+                .expect("invalid order");
+            ids.push(report.order_id);
+            check(&book);
+        }
+
+        let indicative = book.indicative_equilibrium().expect("book is crossed");
+        assert_eq!(indicative.price, dec!(100.00));
+        assert_eq!(indicative.matched_volume, dec!(12));
+        assert_eq!(indicative.imbalance, dec!(6));
+        assert_eq!(indicative.imbalance_side, Some(OrderSide::Buy));
+
+        // Cancels inside and outside the crossed range
+        for id in [ids[3], ids[5], ids[2]] {
+            book.cancel_limit_order(id).expect("order rests");
+            check(&book);
+        }
+        book.set_reference_price(dec!(100.60)).expect("valid price");
+        check(&book);
+
+        book.uncross().expect("book is in an auction");
+        assert_eq!(book.indicative_equilibrium(), None);
+    }
+
+    // The running curve as (price, bids at or above, asks at or below)
+    fn curve(depth: &AuctionDepth) -> Vec<(Decimal, Decimal, Decimal)> {
+        depth
+            .curve
+            .iter()
+            .map(|(tick, (bids, asks))| (tick.level(), *bids, *asks))
+            .collect()
+    }
+
+    #[test]
+    fn test_running_depth_matches_rebuild() {
+        let mut book = auction_book();
+        let mut resting = Vec::new();
+        let mut seed = 7u64;
+        let mut next = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+
+        for _ in 0..400 {
+            if next(3) == 0 && !resting.is_empty() {
+                let id = resting.swap_remove(next(resting.len() as u64) as usize);
+                book.cancel_limit_order(id).expect("order rests");
+            } else {
+                let side = if next(2) == 0 {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                };
+                let price = Decimal::from(90 + next(21));
+                let quantity = Decimal::from(1 + next(9));
+                let report = book
+                    .add_limit_order(side, price, quantity)
+                    .expect("valid order");
+                resting.push(report.order_id);
+            }
+
+            let running = book.auction_depth.as_ref().expect("auction is running");
+            let rebuilt = AuctionDepth::new(&book.bids, &book.asks);
+            assert_eq!(curve(running), curve(&rebuilt));
+            assert_eq!(book.indicative_equilibrium(), book.auction_equilibrium());
+        }
+    }
+
+    #[test]
+    fn test_uncross_without_cross() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        assert_eq!(
+            book.uncross(),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Continuous))
+        );
+
+        book.start_auction().expect("book starts continuous");
+        assert_eq!(
+            book.start_auction(),
+            Err(BookError::InvalidTransition {
+                from: TradingPhase::Auction,
+                to: TradingPhase::Auction,
+            })
+        );
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("invalid order");
+
+        let result = book.uncross().expect("book is in an auction");
+        assert_eq!(result.equilibrium, None);
+        assert!(result.fills.is_empty());
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+        assert_eq!(book.spread(), Some(dec!(1.00)));
+    }
+}
@@ -0,0 +1,246 @@
+use crate::error::BookError;
+use crate::event::BookEvent;
+use crate::order::OrderSide;
+use crate::order_book::OrderBook;
+use crate::phase::TradingPhase;
+use crate::ticks::{Tick, TickPolicy};
+
+use rust_decimal::Decimal;
+
+/// What happens to a limit order priced beyond the band.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum BandAction {
+    /// Reject the order with `BookError::OutsidePriceBand`
+    #[default]
+    Reject,
+    /// Move the price to the band limit (on a tick inside the band)
+    Cap,
+}
+
+/// Limit-up/limit-down protection around the book's reference price.
+///
+/// The band spans `width` either side of the reference price, as a
+/// fraction of it: a width of 0.05 around 100.00 allows trades from 95.00
+/// to 105.00. The reference price is the last trade price, or the value
+/// given to `set_reference_price`, so the band follows the market. A book
+/// without a reference price has no band.
+///
+/// Only the aggressive direction is limited: buys above the upper limit
+/// and sells below the lower limit. Limit orders beyond it are rejected or
+/// capped according to the `action`. A market order that would trade
+/// through the band fills up to the limit and the book pauses: it moves to
+/// [`TradingPhase::Halted`] until it is reopened with an auction. A market
+/// order that is rejected, or stops at the band before filling anything,
+/// leaves trading running.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{BandAction, BookError, OrderBook, OrderSide, PriceBand};
+/// let band = PriceBand::new(dec!(0.05)).unwrap().with_action(BandAction::Cap);
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap().with_price_band(band);
+/// book.set_reference_price(dec!(100.00)).unwrap();
+/// assert_eq!(book.price_band_limits(), Some((dec!(95.00), dec!(105.00))));
+///
+/// // A fat-fingered buy at 150.00 rests at the upper limit instead
+/// book.add_limit_order(OrderSide::Buy, dec!(150.00), dec!(1)).unwrap();
+/// assert_eq!(book.best_bid(), Some(dec!(105.00)));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PriceBand {
+    width: Decimal,
+    action: BandAction,
+}
+
+impl PriceBand {
+    /// A band of `width` either side of the reference price, as a fraction
+    /// above 0 and at most 1
+    pub fn new(width: Decimal) -> Result<Self, BookError> {
+        if width <= Decimal::ZERO || width > Decimal::ONE {
+            return Err(BookError::InvalidPercentage);
+        }
+
+        Ok(Self {
+            width,
+            action: BandAction::default(),
+        })
+    }
+
+    pub fn with_action(mut self, action: BandAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    pub fn width(&self) -> Decimal {
+        self.width
+    }
+
+    pub fn action(&self) -> BandAction {
+        self.action
+    }
+}
+
+impl OrderBook {
+    /// Protect the book with a price band, see [`PriceBand`]
+    pub fn with_price_band(mut self, band: PriceBand) -> Self {
+        self.price_band = Some(band);
+        self
+    }
+
+    pub fn price_band(&self) -> Option<PriceBand> {
+        self.price_band
+    }
+
+    /// Lowest and highest price the book currently trades at, None without
+    /// a band or a reference price
+    pub fn price_band_limits(&self) -> Option<(Decimal, Decimal)> {
+        let band = self.price_band?;
+        let reference = self.reference_price?;
+        let distance = reference * band.width;
+        Some((reference - distance, reference + distance))
+    }
+
+    // Furthest price an order on `side` may trade at on arrival. Auctions
+    // clear at a single price of their own, so only continuous matching is
+    // limited.
+    pub(crate) fn band_limit(&self, side: OrderSide) -> Option<Decimal> {
+        if !self.phase.matches_on_arrival() {
+            return None;
+        }
+
+        let (lower, upper) = self.price_band_limits()?;
+        Some(match side {
+            OrderSide::Buy => upper,
+            OrderSide::Sell => lower,
+        })
+    }
+
+    // Reject or cap a limit order priced beyond the band
+    pub(crate) fn apply_price_band(&self, side: OrderSide, tick: Tick) -> Result<Tick, BookError> {
+        let (Some(band), Some(limit)) = (self.price_band, self.band_limit(side)) else {
+            return Ok(tick);
+        };
+
+        let beyond = match side {
+            OrderSide::Buy => tick.level() > limit,
+            OrderSide::Sell => tick.level() < limit,
+        };
+        if !beyond {
+            return Ok(tick);
+        }
+
+        match band.action {
+            BandAction::Reject => Err(BookError::OutsidePriceBand),
+            // Conservative rounding keeps the capped price inside the band
+            BandAction::Cap => {
+                Tick::for_order(limit, &self.spec.tick_table, side, TickPolicy::Conservative)
+            }
+        }
+    }
+
+    // An order on `side` would have traded through the band `limit`: pause
+    // continuous trading
+    pub(crate) fn pause_at_band(&mut self, side: OrderSide, limit: Decimal) {
+        self.events
+            .push(BookEvent::PriceBandReached { side, limit });
+        if self.phase.can_transition_to(TradingPhase::Halted) {
+            self.set_phase(TradingPhase::Halted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::{MarketOrderOptions, OrderStatus};
+    use rust_decimal_macros::dec;
+
+    fn banded_book(action: BandAction) -> OrderBook {
+        let band = PriceBand::new(dec!(0.05))
+            .expect("valid width")
+            .with_action(action);
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_price_band(band);
+        book.set_reference_price(dec!(100.00))
+            .expect("positive price");
+        book
+    }
+
+    #[test]
+    fn test_band_rejects_and_caps_limit_orders() {
+        assert_eq!(PriceBand::new(dec!(0)), Err(BookError::InvalidPercentage));
+
+        let mut book = banded_book(BandAction::Reject);
+        assert_eq!(
+            book.add_limit_order(OrderSide::Buy, dec!(105.01), dec!(1)),
+            Err(BookError::OutsidePriceBand)
+        );
+        assert_eq!(
+            book.add_limit_order(OrderSide::Sell, dec!(94.99), dec!(1)),
+            Err(BookError::OutsidePriceBand)
+        );
+        // Passive prices beyond the band are harmless
+        book.add_limit_order(OrderSide::Buy, dec!(90.00), dec!(1))
+            .expect("passive buy");
+        book.add_limit_order(OrderSide::Sell, dec!(105.00), dec!(1))
+            .expect("at the limit");
+
+        let mut book = banded_book(BandAction::Cap);
+        book.add_limit_order(OrderSide::Sell, dec!(104.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(106.00), dec!(5))
+            .expect("invalid order");
+        let report = book
+            .add_limit_order(OrderSide::Buy, dec!(200.00), dec!(10))
+            .expect("capped, not rejected");
+        assert_eq!(report.filled_quantity, dec!(5));
+        assert_eq!(book.best_bid(), Some(dec!(105.00)));
+        assert_eq!(book.best_ask(), Some(dec!(106.00)));
+    }
+
+    #[test]
+    fn test_market_order_pauses_at_band() {
+        let mut book = banded_book(BandAction::Reject);
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(104.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(120.00), dec!(5))
+            .expect("invalid order");
+
+        // The band moves with the last trade
+        book.execute_market_order(OrderSide::Buy, dec!(1))
+            .expect("within the band");
+        assert_eq!(book.price_band_limits(), Some((dec!(95.95), dec!(106.05))));
+        book.drain_events();
+
+        // All-or-nothing orders are rejected without touching the book, and
+        // since nothing traded up to the band trading carries on
+        assert_eq!(
+            book.execute_market_order(OrderSide::Buy, dec!(14)),
+            Err(BookError::PriceBandReached)
+        );
+        assert_eq!(book.best_ask(), Some(dec!(101.00)));
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+        assert!(book.drain_events().is_empty());
+
+        // Sweeping up to the band with partial fills allowed pauses trading
+        let options = MarketOrderOptions {
+            allow_partial: true,
+            max_slippage: None,
+        };
+        let report = book
+            .execute_market_order_with(OrderSide::Buy, dec!(14), options)
+            .expect("partial fills allowed");
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.filled_quantity, dec!(9));
+        assert_eq!(report.reject_reason, Some(BookError::PriceBandReached));
+        assert_eq!(book.best_ask(), Some(dec!(120.00)));
+        assert_eq!(book.phase(), TradingPhase::Halted);
+        assert!(book.drain_events().contains(&BookEvent::PhaseChanged {
+            from: TradingPhase::Continuous,
+            to: TradingPhase::Halted,
+        }));
+    }
+}
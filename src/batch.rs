@@ -0,0 +1,160 @@
+use crate::auction::UncrossResult;
+use crate::error::BookError;
+use crate::order_book::OrderBook;
+use crate::phase::TradingPhase;
+
+use std::time::Duration;
+
+// When the running batch auction closes
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) struct BatchSchedule {
+    pub(crate) interval: Duration,
+    pub(crate) ends_at: Duration,
+}
+
+impl OrderBook {
+    /// Switch from continuous matching to frequent batch auctions.
+    ///
+    /// The book enters the auction phase and collects orders for
+    /// `interval`, then clears everything that crosses at a single price
+    /// with the same uncrossing logic as `uncross`, and starts the next
+    /// batch. Batches are cleared either by `poll` once the book's clock
+    /// passes the end of the batch, or explicitly with `clear_batch`.
+    ///
+    /// Batch mode ends with `uncross`, which clears a final batch and
+    /// resumes continuous trading, or with any other phase change.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{ManualClock, OrderBook, OrderSide};
+    /// let clock = ManualClock::new();
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap().with_clock(clock.clone());
+    /// book.start_batch_auctions(Duration::from_millis(100)).unwrap();
+    ///
+    /// book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+    /// assert!(book.poll().is_none()); // Batch still open
+    ///
+    /// clock.advance(Duration::from_millis(100));
+    /// let result = book.poll().unwrap();
+    /// assert_eq!(result.fills.len(), 1);
+    /// assert_eq!(book.batch_ends_at(), Some(Duration::from_millis(200)));
+    /// ```
+    pub fn start_batch_auctions(&mut self, interval: Duration) -> Result<(), BookError> {
+        if interval.is_zero() {
+            return Err(BookError::InvalidInterval);
+        }
+
+        self.transition_to(TradingPhase::Auction)?;
+        self.batch = Some(BatchSchedule {
+            interval,
+            ends_at: self.clock.now() + interval,
+        });
+        Ok(())
+    }
+
+    /// When the current batch closes, None outside batch mode
+    pub fn batch_ends_at(&self) -> Option<Duration> {
+        self.batch.map(|batch| batch.ends_at)
+    }
+
+    /// Clear the current batch now, whatever the clock says, and start the
+    /// next one.
+    pub fn clear_batch(&mut self) -> Result<UncrossResult, BookError> {
+        let Some(batch) = self.batch.as_mut() else {
+            return Err(BookError::BatchModeInactive);
+        };
+        batch.ends_at = self.clock.now() + batch.interval;
+
+        Ok(self.execute_uncross())
+    }
+
+    /// Run whatever is due according to the book's clock: clear a batch
+    /// or end a volatility auction (see [`crate::VolatilityBreaker`]).
+    /// Returns the result of the uncross, if one was due.
+    ///
+    /// Call this regularly, e.g. from the event loop. If several intervals
+    /// passed since the last call, a single batch is cleared and the next
+    /// one ends a full interval from now.
+    pub fn poll(&mut self) -> Option<UncrossResult> {
+        let Some(batch) = self.batch else {
+            return self.poll_volatility_auction();
+        };
+        if self.clock.now() < batch.ends_at {
+            return None;
+        }
+
+        self.clear_batch().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::order::OrderSide;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_batch_auctions() {
+        let clock = ManualClock::new();
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_clock(clock.clone());
+        let interval = Duration::from_millis(100);
+
+        assert_eq!(
+            book.start_batch_auctions(Duration::ZERO),
+            Err(BookError::InvalidInterval)
+        );
+        assert_eq!(book.clear_batch(), Err(BookError::BatchModeInactive));
+        book.start_batch_auctions(interval)
+            .expect("book starts continuous");
+
+        // First batch: 15 trades at a uniform price
+        book.add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(99.00), dec!(15))
+            .expect("invalid order");
+        clock.advance(Duration::from_millis(99));
+        assert!(book.poll().is_none());
+
+        clock.advance(Duration::from_millis(1));
+        let result = book.poll().expect("batch is due");
+        let equilibrium = result.equilibrium.expect("book was crossed");
+        assert_eq!(equilibrium.matched_volume, dec!(15));
+        assert!(result
+            .fills
+            .iter()
+            .all(|fill| fill.price == equilibrium.price));
+
+        // Still collecting: the next order rests even though it crosses
+        assert_eq!(book.phase(), TradingPhase::Auction);
+        assert_eq!(book.batch_ends_at(), Some(Duration::from_millis(200)));
+        let report = book
+            .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(5))
+            .expect("invalid order");
+        assert!(report.fills.is_empty());
+
+        // An explicit tick clears early and restarts the interval
+        clock.advance(Duration::from_millis(30));
+        let result = book.clear_batch().expect("batch mode is on");
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(book.batch_ends_at(), Some(Duration::from_millis(230)));
+
+        // Nothing crossed: the batch clears empty
+        clock.advance(Duration::from_millis(500));
+        let result = book.poll().expect("batch is due");
+        assert_eq!(result.equilibrium, None);
+        assert_eq!(book.batch_ends_at(), Some(Duration::from_millis(730)));
+
+        // Uncrossing returns to continuous matching
+        book.uncross().expect("book is in an auction");
+        assert_eq!(book.batch_ends_at(), None);
+        assert!(book.poll().is_none());
+    }
+}
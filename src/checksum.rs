@@ -0,0 +1,252 @@
+use crate::order_book::OrderBook;
+use crate::replica::ReplicaBook;
+
+use rust_decimal::Decimal;
+
+/// The string layout a venue uses before taking the CRC32 of its book.
+///
+/// Kraken renders values at the pair's fixed price and quantity precision,
+/// so they are rounded and padded to it whatever scale the `Decimal`
+/// carries. OKX sends minimal strings, so trailing zeros are stripped
+/// first.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ChecksumFormat {
+    /// OKX: bid and ask levels interleaved best first as
+    /// `bidPx:bidSz:askPx:askSz:...`, skipping a side once it runs out,
+    /// with trailing zeros removed (`3366.0` is written `3366`).
+    /// OKX publishes the result as a signed 32-bit integer, use
+    /// `checksum as i32` to compare.
+    Okx,
+    /// Kraken: all ask levels best first, then all bid levels best first,
+    /// each as price followed by quantity with the decimal point removed
+    /// and leading zeros stripped, concatenated without separators.
+    /// Prices are written with `price_decimals` decimals and quantities
+    /// with `qty_decimals`, the pair's precision from Kraken's asset pairs.
+    Kraken {
+        price_decimals: u32,
+        qty_decimals: u32,
+    },
+}
+
+/// Compute a venue-style CRC32 checksum over `(price, quantity)` levels.
+///
+/// Both slices are expected best price first, exactly as returned by
+/// `bid_levels`/`ask_levels`. Callers choose how many levels to pass,
+/// matching the depth the venue checksums (25 for OKX, 10 for Kraken).
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::checksum::{checksum, ChecksumFormat};
+/// let bids = [(dec!(3366.1), dec!(7)), (dec!(3366), dec!(6))];
+/// let asks = [(dec!(3366.8), dec!(9)), (dec!(3368), dec!(8))];
+///
+/// // Same levels always produce the same checksum
+/// assert_eq!(
+///     checksum(&bids, &asks, ChecksumFormat::Okx),
+///     checksum(&bids, &asks, ChecksumFormat::Okx),
+/// );
+/// ```
+pub fn checksum(
+    bids: &[(Decimal, Decimal)],
+    asks: &[(Decimal, Decimal)],
+    format: ChecksumFormat,
+) -> u32 {
+    crc32(checksum_payload(bids, asks, format).as_bytes())
+}
+
+// The exact string the venue feeds into CRC32
+fn checksum_payload(
+    bids: &[(Decimal, Decimal)],
+    asks: &[(Decimal, Decimal)],
+    format: ChecksumFormat,
+) -> String {
+    match format {
+        ChecksumFormat::Okx => {
+            let mut fields = Vec::with_capacity(2 * (bids.len() + asks.len()));
+            for i in 0..bids.len().max(asks.len()) {
+                if let Some((price, quantity)) = bids.get(i) {
+                    fields.push(price.normalize().to_string());
+                    fields.push(quantity.normalize().to_string());
+                }
+                if let Some((price, quantity)) = asks.get(i) {
+                    fields.push(price.normalize().to_string());
+                    fields.push(quantity.normalize().to_string());
+                }
+            }
+            fields.join(":")
+        }
+        ChecksumFormat::Kraken {
+            price_decimals,
+            qty_decimals,
+        } => {
+            let mut payload = String::new();
+            for (price, quantity) in asks.iter().chain(bids.iter()) {
+                payload.push_str(&kraken_field(*price, price_decimals));
+                payload.push_str(&kraken_field(*quantity, qty_decimals));
+            }
+            payload
+        }
+    }
+}
+
+fn kraken_field(value: Decimal, decimals: u32) -> String {
+    let mut value = value.round_dp(decimals);
+    value.rescale(decimals);
+    let digits = value.to_string().replace('.', "");
+    let trimmed = digits.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+// Standard CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320)
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+impl OrderBook {
+    /// Checksum over the top `depth` levels of each side.
+    ///
+    /// Two books with the same aggregate depth produce the same checksum,
+    /// which makes this a cheap equality check between books, e.g. a
+    /// matching engine and its market-data replica.
+    pub fn checksum(&self, depth: usize, format: ChecksumFormat) -> u32 {
+        checksum(&self.bid_levels(depth), &self.ask_levels(depth), format)
+    }
+}
+
+impl ReplicaBook {
+    /// Checksum over the top `depth` levels of each side, to be compared
+    /// against the checksum published with the venue's depth update.
+    pub fn checksum(&self, depth: usize, format: ChecksumFormat) -> u32 {
+        checksum(&self.bid_levels(depth), &self.ask_levels(depth), format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::OrderSide;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_crc32_reference_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_checksum_payload_formats() {
+        let bids = [(dec!(3366.1), dec!(7)), (dec!(3366.0), dec!(6.00))];
+        let asks = [
+            (dec!(3366.8), dec!(9)),
+            (dec!(3368), dec!(8)),
+            (dec!(3372), dec!(8)),
+        ];
+
+        assert_eq!(
+            checksum_payload(&bids, &asks, ChecksumFormat::Okx),
+            "3366.1:7:3366.8:9:3366:6:3368:8:3372:8"
+        );
+
+        // The scale a value carries doesn't matter, the pair's precision does
+        let kraken = ChecksumFormat::Kraken {
+            price_decimals: 5,
+            qty_decimals: 8,
+        };
+        let bids = [(dec!(0.05), dec!(0.000005))];
+        let asks = [
+            (dec!(0.05005), dec!(0.00000500)),
+            (dec!(0.050100), dec!(1.5)),
+        ];
+        assert_eq!(
+            checksum_payload(&bids, &asks, kraken),
+            "500550050101500000005000500"
+        );
+    }
+
+    #[test]
+    fn test_kraken_documented_example() {
+        // Sample book from Kraken's WebSocket API guide "How to calculate
+        // the checksum", published checksum 974947235
+        let asks: Vec<_> = [
+            dec!(0.05005),
+            dec!(0.05010),
+            dec!(0.05015),
+            dec!(0.05020),
+            dec!(0.05025),
+            dec!(0.05030),
+            dec!(0.05035),
+            dec!(0.05040),
+            dec!(0.05045),
+            dec!(0.05050),
+        ]
+        .into_iter()
+        .map(|price| (price, dec!(0.00000500)))
+        .collect();
+        let bids: Vec<_> = [
+            dec!(0.05000),
+            dec!(0.04995),
+            dec!(0.04990),
+            dec!(0.04980),
+            dec!(0.04975),
+            dec!(0.04970),
+            dec!(0.04965),
+            dec!(0.04960),
+            dec!(0.04955),
+            dec!(0.04950),
+        ]
+        .into_iter()
+        .map(|price| (price, dec!(0.000005)))
+        .collect();
+
+        let kraken = ChecksumFormat::Kraken {
+            price_decimals: 5,
+            qty_decimals: 8,
+        };
+        assert_eq!(checksum(&bids, &asks, kraken), 974947235);
+    }
+
+    #[test]
+    fn test_replica_matches_order_book() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(7))
+            .expect("invalid order");
+
+        let mut replica = ReplicaBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        replica
+            .apply_snapshot(1, &[(dec!(99.00), dec!(15))], &[(dec!(101.00), dec!(7))])
+            .expect("valid snapshot");
+
+        let kraken = ChecksumFormat::Kraken {
+            price_decimals: 2,
+            qty_decimals: 8,
+        };
+        for format in [ChecksumFormat::Okx, kraken] {
+            assert_eq!(book.checksum(25, format), replica.checksum(25, format));
+        }
+
+        // Any difference in depth changes the checksum
+        book.add_limit_order(OrderSide::Sell, dec!(102.00), dec!(1))
+            .expect("invalid order");
+        assert_ne!(
+            book.checksum(25, ChecksumFormat::Okx),
+            replica.checksum(25, ChecksumFormat::Okx)
+        );
+    }
+}
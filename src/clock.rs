@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of the current time for anything the book schedules (batch
+/// auctions, volatility windows).
+///
+/// Times are durations since an arbitrary fixed origin, only differences
+/// between them matter.
+///
+/// Clocks must be `Send + Sync` so the book can move to a matching thread.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Duration;
+}
+
+/// Wall clock time since the Unix epoch. The default for every book.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// A clock that only moves when told to, for simulations, replays and
+/// tests.
+///
+/// Clones share the same time, so a caller can keep one handle and give
+/// another to the book, on the same thread or another one.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use limitbook::{Clock, ManualClock};
+/// let clock = ManualClock::new();
+/// let handle = clock.clone();
+///
+/// handle.advance(Duration::from_millis(250));
+/// assert_eq!(clock.now(), Duration::from_millis(250));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// A clock starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.time() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.time() += by;
+    }
+
+    // The shared time. Nothing can panic while holding it but an overflowing
+    // `advance`, which leaves the time untouched, so poisoning is ignored.
+    fn time(&self) -> MutexGuard<'_, Duration> {
+        self.now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.time()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_manual_clock_shared_across_threads() {
+        let clock = ManualClock::new();
+        let handle = clock.clone();
+        thread::spawn(move || handle.advance(Duration::from_secs(5)))
+            .join()
+            .expect("thread panicked");
+        assert_eq!(clock.now(), Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::order::{OrderId, OwnerId};
+use crate::phase::TradingPhase;
+use crate::risk::RiskViolation;
+
+use std::fmt;
+
+/// Every reason the book can reject a request.
+///
+/// Variants are stable so gateways can map them to venue reject codes
+/// without parsing messages. New variants may be added as the book grows,
+/// so matches need a wildcard arm.
+///
+/// `BookError` implements `std::error::Error`, so `?` converts it into an
+/// `eyre::Report` (or any other boxed error) where that is more convenient.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{BookError, OrderBook, OrderSide};
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+///
+/// let err = book.execute_market_order(OrderSide::Buy, dec!(10)).unwrap_err();
+/// assert_eq!(err, BookError::InsufficientLiquidity);
+///
+/// // Still usable with eyre
+/// fn cancel(book: &mut OrderBook) -> eyre::Result<()> {
+///     book.cancel_limit_order(42)?;
+///     Ok(())
+/// }
+/// assert_eq!(cancel(&mut book).unwrap_err().to_string(), "Order not found");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum BookError {
+    /// Price is zero or negative
+    InvalidPrice,
+    /// Quantity is zero or negative
+    InvalidQuantity,
+    /// Tick size is zero or negative
+    InvalidTickSize,
+    /// Tick table bands do not start at zero, do not increase, or have a
+    /// boundary that is not on a tick of both neighbouring bands
+    InvalidTickTable,
+    /// Notional budget is zero or negative
+    InvalidNotional,
+    /// Lot size is zero or negative
+    InvalidLotSize,
+    /// Maximum slippage is negative
+    InvalidSlippage,
+    /// Percentage is not above 0 and at most 1
+    InvalidPercentage,
+    /// Maker and taker rates add up to less than zero
+    InvalidFeeRate,
+    /// Fee tier volume is not positive or is already used by another tier
+    InvalidFeeTier,
+    /// A time interval is zero
+    InvalidInterval,
+    /// Price does not sit on a tick
+    PriceNotAligned,
+    /// No live order with this id, it never existed or was filled or cancelled
+    OrderNotFound(OrderId),
+    /// The book has no level at the price an order was recorded at
+    TickLevelNotFound,
+    /// Not enough resting volume to fill a market order completely
+    InsufficientLiquidity,
+    /// Matching stopped at a market order's protection price
+    ProtectionPriceReached,
+    /// A notional budget too small to buy one lot at the best price
+    NotionalBelowLot,
+    /// Limit price is beyond the price band
+    OutsidePriceBand,
+    /// Matching stopped at the price band, see `PriceBand`
+    PriceBandReached,
+    /// Matching stopped at the volatility breaker limit and the book
+    /// entered a volatility auction, see `VolatilityBreaker`
+    VolatilityInterruption,
+    /// The operation is not accepted in the book's current trading phase
+    NotAllowedInPhase(TradingPhase),
+    /// The book can't move between these two trading phases
+    InvalidTransition {
+        from: TradingPhase,
+        to: TradingPhase,
+    },
+    /// The book is not running frequent batch auctions
+    BatchModeInactive,
+    /// Quantity is not a multiple of the instrument's lot size
+    LotSizeViolation,
+    /// Quantity is below the instrument's minimum order size
+    BelowMinQuantity,
+    /// Quantity is above the instrument's maximum order size
+    AboveMaxQuantity,
+    /// Price * quantity is below the instrument's minimum notional
+    BelowMinNotional,
+    /// The order breaks one of its account's pre-trade limits
+    RiskLimitBreached(RiskViolation),
+    /// The account is blocked by the kill switch
+    AccountBlocked(OwnerId),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            BookError::InvalidPrice => "Price must be positive",
+            BookError::InvalidQuantity => "Quantity must be positive",
+            BookError::InvalidTickSize => "Tick size must be positive",
+            BookError::InvalidTickTable => "Invalid tick table",
+            BookError::InvalidNotional => "Notional must be positive",
+            BookError::InvalidLotSize => "Lot size must be positive",
+            BookError::InvalidSlippage => "Maximum slippage must not be negative",
+            BookError::InvalidPercentage => "Percentage must be above 0 and at most 1",
+            BookError::InvalidFeeRate => "Maker and taker rates must not add up to less than zero",
+            BookError::InvalidFeeTier => "Fee tier volume must be positive and unique",
+            BookError::InvalidInterval => "Interval must be positive",
+            BookError::PriceNotAligned => "Price not aligned to tick size",
+            BookError::OrderNotFound(_) => "Order not found",
+            BookError::TickLevelNotFound => "Tick level not found",
+            BookError::InsufficientLiquidity => "Insufficient liquidity for market order",
+            BookError::ProtectionPriceReached => "Protection price reached",
+            BookError::NotionalBelowLot => "Notional too small for one lot",
+            BookError::OutsidePriceBand => "Price outside the price band",
+            BookError::PriceBandReached => "Price band reached",
+            BookError::VolatilityInterruption => "Volatility interruption",
+            BookError::NotAllowedInPhase(_) => "Not allowed in the current trading phase",
+            BookError::InvalidTransition { .. } => "Invalid trading phase transition",
+            BookError::BatchModeInactive => "Batch auction mode is not active",
+            BookError::LotSizeViolation => "Quantity not a multiple of lot size",
+            BookError::BelowMinQuantity => "Quantity below minimum order size",
+            BookError::AboveMaxQuantity => "Quantity above maximum order size",
+            BookError::BelowMinNotional => "Notional below minimum",
+            BookError::RiskLimitBreached(_) => "Pre-trade risk limit breached",
+            BookError::AccountBlocked(_) => "Account blocked by kill switch",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for BookError {}
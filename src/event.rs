@@ -0,0 +1,62 @@
+use crate::error::BookError;
+use crate::order::{ExecutionReport, OrderId, OrderSide, OwnerId};
+use crate::order_book::OrderBook;
+use crate::phase::TradingPhase;
+
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+/// Something that happened to the book that downstream consumers (market
+/// data, surveillance, gateways) should hear about.
+///
+/// Events queue up inside the book in the order they happened until they
+/// are collected with `drain_events`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum BookEvent {
+    /// The trading phase changed
+    PhaseChanged {
+        from: TradingPhase,
+        to: TradingPhase,
+    },
+    /// An order on `side` would have traded beyond the price band `limit`,
+    /// and continuous trading pauses
+    PriceBandReached { side: OrderSide, limit: Decimal },
+    /// An order on `side` would have traded beyond the volatility breaker
+    /// `limit`, and a volatility auction runs until `ends_at` on the book's
+    /// clock
+    VolatilityInterruption {
+        side: OrderSide,
+        limit: Decimal,
+        ends_at: Duration,
+    },
+    /// The kill switch cancelled a resting or stop order of `owner`.
+    /// `price` is the limit or stop price and `quantity` what was left of
+    /// the order. Orders cancelled through `cancel_limit_order`,
+    /// `cancel_stop_order` or by a tick size change are reported to the
+    /// caller and emit no event.
+    OrderCancelled {
+        order_id: OrderId,
+        owner: OwnerId,
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    },
+    /// A stop order reached its stop price and executed as a market order
+    /// with this result
+    StopTriggered {
+        order_id: OrderId,
+        result: Result<ExecutionReport, BookError>,
+    },
+    /// The kill switch blocked `owner`
+    AccountBlocked { owner: OwnerId },
+    /// `owner` may submit orders again
+    AccountReenabled { owner: OwnerId },
+}
+
+impl OrderBook {
+    /// Take every event emitted since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<BookEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
@@ -0,0 +1,297 @@
+use crate::error::BookError;
+use crate::order::{Fill, OwnerId};
+use crate::order_book::OrderBook;
+
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+/// The currency fees are charged in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum FeeCurrency {
+    /// A rate of the fill's notional, price * quantity
+    #[default]
+    Quote,
+    /// A rate of the fill's quantity
+    Base,
+}
+
+/// Maker and taker rates of one fee tier, as fractions (0.001 is 10 bps).
+/// A negative maker rate is a rebate.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct FeeRates {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+impl FeeRates {
+    // A trade between two parties of this tier never pays out more than it
+    // collects
+    fn validate(&self) -> Result<(), BookError> {
+        if self.maker + self.taker < Decimal::ZERO {
+            return Err(BookError::InvalidFeeRate);
+        }
+
+        Ok(())
+    }
+}
+
+/// Maker/taker fees charged on every fill, with volume tiers per owner.
+///
+/// An owner's tier is picked by the notional they traded, as maker or
+/// taker, over the rolling `volume_window` (30 days by default) before the
+/// order that is matching. Anonymous orders always pay the base rates.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{FeeSchedule, OrderBook, OrderSide};
+/// let fees = FeeSchedule::new(dec!(-0.0001), dec!(0.0005))
+///     .unwrap()
+///     .with_tier(dec!(1000), dec!(-0.0002), dec!(0.0003))
+///     .unwrap();
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap().with_fee_schedule(fees);
+///
+/// book.add_limit_order_for(1, OrderSide::Sell, dec!(100.00), dec!(20)).unwrap();
+/// let report = book.add_limit_order_for(2, OrderSide::Buy, dec!(100.00), dec!(10)).unwrap();
+/// let fill = &report.fills[0];
+/// assert_eq!(fill.taker_fee, dec!(0.5));
+/// assert_eq!(fill.maker_fee, dec!(-0.1)); // Rebate
+///
+/// // Owner 1 has now traded 1000 and moved up a tier
+/// let report = book.add_limit_order_for(2, OrderSide::Buy, dec!(100.00), dec!(10)).unwrap();
+/// assert_eq!(report.fills[0].maker_fee, dec!(-0.2));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FeeSchedule {
+    tiers: Vec<(Decimal, FeeRates)>, // Minimum rolling volume, ascending from zero
+    currency: FeeCurrency,
+    volume_window: Duration,
+}
+
+impl FeeSchedule {
+    /// A schedule charging `maker_rate` and `taker_rate` to everyone
+    pub fn new(maker_rate: Decimal, taker_rate: Decimal) -> Result<Self, BookError> {
+        let rates = FeeRates {
+            maker: maker_rate,
+            taker: taker_rate,
+        };
+        rates.validate()?;
+
+        Ok(Self {
+            tiers: vec![(Decimal::ZERO, rates)],
+            currency: FeeCurrency::default(),
+            volume_window: Duration::from_secs(30 * 24 * 60 * 60),
+        })
+    }
+
+    /// Charge owners who traded at least `min_volume` over the window
+    /// these rates instead
+    pub fn with_tier(
+        mut self,
+        min_volume: Decimal,
+        maker_rate: Decimal,
+        taker_rate: Decimal,
+    ) -> Result<Self, BookError> {
+        let rates = FeeRates {
+            maker: maker_rate,
+            taker: taker_rate,
+        };
+        rates.validate()?;
+
+        if min_volume <= Decimal::ZERO || self.tiers.iter().any(|(volume, _)| *volume == min_volume)
+        {
+            return Err(BookError::InvalidFeeTier);
+        }
+
+        self.tiers.push((min_volume, rates));
+        self.tiers.sort_by_key(|(volume, _)| *volume);
+        Ok(self)
+    }
+
+    pub fn with_currency(mut self, currency: FeeCurrency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// How far back traded volume counts towards an owner's tier
+    pub fn with_volume_window(mut self, volume_window: Duration) -> Result<Self, BookError> {
+        if volume_window.is_zero() {
+            return Err(BookError::InvalidInterval);
+        }
+
+        self.volume_window = volume_window;
+        Ok(self)
+    }
+
+    pub fn currency(&self) -> FeeCurrency {
+        self.currency
+    }
+
+    pub fn volume_window(&self) -> Duration {
+        self.volume_window
+    }
+
+    /// The rates of the highest tier `volume` qualifies for
+    pub fn rates_for(&self, volume: Decimal) -> FeeRates {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|(min_volume, _)| volume >= *min_volume)
+            .map(|(_, rates)| *rates)
+            .expect("the base tier starts at zero")
+    }
+
+    // Fee at `rate` on `quantity` at `price`
+    fn charge(&self, rate: Decimal, price: Decimal, quantity: Decimal) -> Decimal {
+        match self.currency {
+            FeeCurrency::Quote => rate * price * quantity,
+            FeeCurrency::Base => rate * quantity,
+        }
+    }
+}
+
+impl OrderBook {
+    /// Charge fees on every fill, see [`FeeSchedule`]
+    pub fn with_fee_schedule(mut self, fee_schedule: FeeSchedule) -> Self {
+        self.fee_schedule = Some(fee_schedule);
+        self
+    }
+
+    pub fn fee_schedule(&self) -> Option<&FeeSchedule> {
+        self.fee_schedule.as_ref()
+    }
+
+    /// Notional `owner` traded over the fee schedule's volume window, zero
+    /// without a fee schedule
+    pub fn rolling_volume(&self, owner: OwnerId) -> Decimal {
+        let (Some(fee_schedule), Some(trades)) =
+            (&self.fee_schedule, self.owner_volumes.get(&owner))
+        else {
+            return Decimal::ZERO;
+        };
+
+        let window_start = self.clock.now().saturating_sub(fee_schedule.volume_window);
+        trades
+            .iter()
+            .filter(|(time, _)| *time >= window_start)
+            .map(|(_, notional)| *notional)
+            .sum()
+    }
+
+    // Work out both parties' fees on a fill at their current tiers
+    pub(crate) fn charge_fees(&self, fill: &mut Fill) {
+        let Some(fee_schedule) = &self.fee_schedule else {
+            return;
+        };
+
+        let rates = |owner: Option<OwnerId>| {
+            let volume = owner.map_or(Decimal::ZERO, |owner| self.rolling_volume(owner));
+            fee_schedule.rates_for(volume)
+        };
+        fill.taker_fee =
+            fee_schedule.charge(rates(fill.taker_owner).taker, fill.price, fill.quantity);
+        fill.maker_fee =
+            fee_schedule.charge(rates(fill.maker_owner).maker, fill.price, fill.quantity);
+        fill.fee_currency = fee_schedule.currency;
+    }
+
+    // Count fills towards their owners' tiers, dropping volume that left
+    // the window
+    pub(crate) fn record_volumes(&mut self, fills: &[Fill]) {
+        let Some(fee_schedule) = &self.fee_schedule else {
+            return;
+        };
+
+        let now = self.clock.now();
+        let window_start = now.saturating_sub(fee_schedule.volume_window);
+        for fill in fills {
+            let notional = fill.price * fill.quantity;
+            for owner in [fill.taker_owner, fill.maker_owner].into_iter().flatten() {
+                let trades = self.owner_volumes.entry(owner).or_default();
+                while trades.front().is_some_and(|(time, _)| *time < window_start) {
+                    trades.pop_front();
+                }
+                trades.push_back((now, notional));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::order::{MarketOrderOptions, OrderSide};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fee_schedule_validation() {
+        assert_eq!(
+            FeeSchedule::new(dec!(-0.001), dec!(0.0005)),
+            Err(BookError::InvalidFeeRate)
+        );
+
+        let schedule = FeeSchedule::new(dec!(0.0002), dec!(0.0005)).expect("valid rates");
+        assert_eq!(
+            schedule.clone().with_tier(dec!(0), dec!(0), dec!(0)),
+            Err(BookError::InvalidFeeTier)
+        );
+
+        let schedule = schedule
+            .with_tier(dec!(5000), dec!(0), dec!(0.0002))
+            .and_then(|schedule| schedule.with_tier(dec!(1000), dec!(0.0001), dec!(0.0004)))
+            .expect("valid tiers");
+        assert_eq!(schedule.rates_for(dec!(999)).taker, dec!(0.0005));
+        assert_eq!(schedule.rates_for(dec!(1000)).taker, dec!(0.0004));
+        assert_eq!(schedule.rates_for(dec!(1e9)).taker, dec!(0.0002));
+    }
+
+    #[test]
+    fn test_fees_on_fills() {
+        let clock = ManualClock::new();
+        let fees = FeeSchedule::new(dec!(-0.001), dec!(0.002))
+            .expect("valid rates")
+            .with_tier(dec!(50), dec!(-0.001), dec!(0.001))
+            .expect("valid tier")
+            .with_currency(FeeCurrency::Base)
+            .with_volume_window(Duration::from_secs(60))
+            .expect("valid window");
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_clock(clock.clone())
+            .with_fee_schedule(fees);
+
+        // Anonymous maker, owned taker sweeping with a market order
+        book.add_limit_order(OrderSide::Sell, dec!(10.00), dec!(4))
+            .expect("invalid order");
+        book.add_limit_order_for(7, OrderSide::Sell, dec!(10.50), dec!(10))
+            .expect("invalid order");
+        let report = book
+            .execute_market_order_for(3, OrderSide::Buy, dec!(6), MarketOrderOptions::default())
+            .expect("enough liquidity");
+
+        let fill = &report.fills[0];
+        assert_eq!((fill.taker_owner, fill.maker_owner), (Some(3), None));
+        assert_eq!(fill.fee_currency, FeeCurrency::Base);
+        assert_eq!(fill.taker_fee, dec!(0.008));
+        assert_eq!(fill.maker_fee, dec!(-0.004));
+        let fill = &report.fills[1];
+        assert_eq!(fill.maker_owner, Some(7));
+        assert_eq!(fill.taker_fee, dec!(0.004));
+        assert_eq!(book.rolling_volume(3), dec!(61));
+        assert_eq!(book.rolling_volume(7), dec!(21));
+
+        // Owner 3 reached the second tier, until the volume ages out
+        let report = book
+            .execute_market_order_for(3, OrderSide::Buy, dec!(1), MarketOrderOptions::default())
+            .expect("enough liquidity");
+        assert_eq!(report.fills[0].taker_fee, dec!(0.001));
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(book.rolling_volume(3), dec!(0));
+        let report = book
+            .execute_notional_market_order_for(3, OrderSide::Buy, dec!(21), dec!(1))
+            .expect("enough liquidity");
+        assert_eq!(report.fills[0].taker_fee, dec!(0.004));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::error::BookError;
+use crate::ticks::TickTable;
+
+use rust_decimal::Decimal;
+
+/// Trading constraints of the instrument a book lists.
+///
+/// Every constraint except the tick size is optional. The book checks
+/// them on every submission path before an order is assigned an id or
+/// reaches the matching loop.
+///
+/// # Fields
+/// * `tick_table` - Minimum price increment, optionally by price band
+/// * `lot_size` - Quantity increment, order quantities must be a multiple of it
+/// * `min_quantity` - Smallest accepted order quantity
+/// * `max_quantity` - Largest accepted order quantity
+/// * `min_notional` - Smallest accepted price * quantity
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{BookError, InstrumentSpec, OrderBook, OrderSide};
+/// let spec = InstrumentSpec::new(dec!(0.01))
+///     .with_lot_size(dec!(0.001))
+///     .with_min_quantity(dec!(0.01))
+///     .with_min_notional(dec!(10));
+/// let mut book = OrderBook::from_spec(spec).unwrap();
+///
+/// assert_eq!(
+///     book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(0.0015)),
+///     Err(BookError::LotSizeViolation)
+/// );
+/// assert_eq!(
+///     book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(0.05)),
+///     Err(BookError::BelowMinNotional)
+/// );
+/// assert!(book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(0.1)).is_ok());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InstrumentSpec {
+    pub tick_table: TickTable,
+    pub lot_size: Option<Decimal>,
+    pub min_quantity: Option<Decimal>,
+    pub max_quantity: Option<Decimal>,
+    pub min_notional: Option<Decimal>,
+}
+
+impl InstrumentSpec {
+    /// A spec with only a tick size and no quantity constraints
+    pub fn new(tick_size: Decimal) -> Self {
+        Self {
+            tick_table: TickTable::fixed(tick_size),
+            lot_size: None,
+            min_quantity: None,
+            max_quantity: None,
+            min_notional: None,
+        }
+    }
+
+    /// Use price-banded tick sizes instead of a single tick size
+    pub fn with_tick_table(mut self, tick_table: TickTable) -> Self {
+        self.tick_table = tick_table;
+        self
+    }
+
+    pub fn with_lot_size(mut self, lot_size: Decimal) -> Self {
+        self.lot_size = Some(lot_size);
+        self
+    }
+
+    pub fn with_min_quantity(mut self, min_quantity: Decimal) -> Self {
+        self.min_quantity = Some(min_quantity);
+        self
+    }
+
+    pub fn with_max_quantity(mut self, max_quantity: Decimal) -> Self {
+        self.max_quantity = Some(max_quantity);
+        self
+    }
+
+    pub fn with_min_notional(mut self, min_notional: Decimal) -> Self {
+        self.min_notional = Some(min_notional);
+        self
+    }
+
+    /// Check the spec itself is consistent.
+    pub fn validate(&self) -> Result<(), BookError> {
+        self.tick_table.validate()?;
+
+        if self
+            .lot_size
+            .is_some_and(|lot_size| lot_size <= Decimal::ZERO)
+        {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        let min_quantity = self.min_quantity.unwrap_or(Decimal::ZERO);
+        if min_quantity < Decimal::ZERO {
+            return Err(BookError::InvalidQuantity);
+        }
+        if let Some(max_quantity) = self.max_quantity {
+            if max_quantity <= Decimal::ZERO || max_quantity < min_quantity {
+                return Err(BookError::InvalidQuantity);
+            }
+        }
+
+        if self
+            .min_notional
+            .is_some_and(|notional| notional < Decimal::ZERO)
+        {
+            return Err(BookError::InvalidNotional);
+        }
+
+        Ok(())
+    }
+
+    // Lot, minimum and maximum checks on an order quantity
+    pub(crate) fn check_quantity(&self, quantity: Decimal) -> Result<(), BookError> {
+        if let Some(lot_size) = self.lot_size {
+            if !(quantity % lot_size).is_zero() {
+                return Err(BookError::LotSizeViolation);
+            }
+        }
+
+        if self.min_quantity.is_some_and(|min| quantity < min) {
+            return Err(BookError::BelowMinQuantity);
+        }
+
+        if self.max_quantity.is_some_and(|max| quantity > max) {
+            return Err(BookError::AboveMaxQuantity);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_notional(&self, notional: Decimal) -> Result<(), BookError> {
+        if self.min_notional.is_some_and(|min| notional < min) {
+            return Err(BookError::BelowMinNotional);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_spec_validation() {
+        assert!(InstrumentSpec::new(dec!(0.01)).validate().is_ok());
+        assert_eq!(
+            InstrumentSpec::new(dec!(0)).validate(),
+            Err(BookError::InvalidTickSize)
+        );
+        assert_eq!(
+            InstrumentSpec::new(dec!(0.01))
+                .with_lot_size(dec!(0))
+                .validate(),
+            Err(BookError::InvalidLotSize)
+        );
+        assert_eq!(
+            InstrumentSpec::new(dec!(0.01))
+                .with_min_quantity(dec!(10))
+                .with_max_quantity(dec!(5))
+                .validate(),
+            Err(BookError::InvalidQuantity)
+        );
+    }
+
+    #[test]
+    fn test_quantity_checks() {
+        let spec = InstrumentSpec::new(dec!(0.01))
+            .with_lot_size(dec!(0.5))
+            .with_min_quantity(dec!(1))
+            .with_max_quantity(dec!(100));
+
+        assert_eq!(spec.check_quantity(dec!(1.5)), Ok(()));
+        assert_eq!(spec.check_quantity(dec!(100)), Ok(()));
+        assert_eq!(
+            spec.check_quantity(dec!(1.25)),
+            Err(BookError::LotSizeViolation)
+        );
+        assert_eq!(
+            spec.check_quantity(dec!(0.5)),
+            Err(BookError::BelowMinQuantity)
+        );
+        assert_eq!(
+            spec.check_quantity(dec!(100.5)),
+            Err(BookError::AboveMaxQuantity)
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use crate::event::BookEvent;
+use crate::order::{OrderId, OwnerId};
+use crate::order_book::OrderBook;
+
+impl OrderBook {
+    /// Pull an account out of the market in one step: cancel every order
+    /// it has resting, and every stop order it has waiting, and reject
+    /// everything it submits until `reenable_account` is called.
+    ///
+    /// Emits a [`BookEvent::OrderCancelled`] for each cancelled order,
+    /// resting orders before stop orders, followed by a
+    /// [`BookEvent::AccountBlocked`]. Returns the ids of the cancelled
+    /// orders in ascending order. Blocking an account that is already
+    /// blocked cancels whatever was left and emits no new `AccountBlocked`.
+    ///
+    /// Only submissions made on the account's behalf (`add_limit_order_for`,
+    /// `execute_market_order_for`, ...) are blocked. Cancels still work.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{BookError, OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order_for(1, OrderSide::Buy, dec!(99.00), dec!(5)).unwrap();
+    /// book.add_limit_order_for(1, OrderSide::Sell, dec!(101.00), dec!(5)).unwrap();
+    ///
+    /// let cancelled = book.kill_switch(1);
+    /// assert_eq!(cancelled, vec![0, 1]);
+    /// assert_eq!(book.best_prices(), (None, None));
+    /// assert_eq!(
+    ///     book.add_limit_order_for(1, OrderSide::Buy, dec!(99.00), dec!(5)),
+    ///     Err(BookError::AccountBlocked(1))
+    /// );
+    ///
+    /// book.reenable_account(1);
+    /// assert!(book.add_limit_order_for(1, OrderSide::Buy, dec!(99.00), dec!(5)).is_ok());
+    /// ```
+    pub fn kill_switch(&mut self, owner: OwnerId) -> Vec<OrderId> {
+        let resting: Vec<_> = self
+            .bids
+            .iter()
+            .chain(self.asks.iter())
+            .flat_map(|(tick, orders)| orders.orders().iter().map(move |order| (tick, order)))
+            .filter(|(_, order)| order.owner == Some(owner))
+            .map(|(tick, order)| (order.id, order.order_side, tick.level(), order.quantity))
+            .collect();
+
+        let mut cancelled = Vec::with_capacity(resting.len());
+        for (order_id, side, price, quantity) in resting {
+            self.cancel_limit_order(order_id)
+                .expect("resting order is in the lookup");
+            self.events.push(BookEvent::OrderCancelled {
+                order_id,
+                owner,
+                side,
+                price,
+                quantity,
+            });
+            cancelled.push(order_id);
+        }
+
+        let (stops, others) = self
+            .stop_orders
+            .drain(..)
+            .partition(|stop| stop.owner == Some(owner));
+        self.stop_orders = others;
+        for stop in stops {
+            self.events.push(BookEvent::OrderCancelled {
+                order_id: stop.id,
+                owner,
+                side: stop.side,
+                price: stop.stop_price,
+                quantity: stop.quantity,
+            });
+            cancelled.push(stop.id);
+        }
+        cancelled.sort_unstable();
+
+        if self.blocked_accounts.insert(owner) {
+            self.events.push(BookEvent::AccountBlocked { owner });
+        }
+        cancelled
+    }
+
+    /// Accept submissions from an account blocked by `kill_switch` again.
+    /// Returns whether it was blocked.
+    ///
+    /// Emits a [`BookEvent::AccountReenabled`] if it was.
+    pub fn reenable_account(&mut self, owner: OwnerId) -> bool {
+        let was_blocked = self.blocked_accounts.remove(&owner);
+        if was_blocked {
+            self.events.push(BookEvent::AccountReenabled { owner });
+        }
+        was_blocked
+    }
+
+    /// Whether submissions from `owner` are rejected by the kill switch
+    pub fn is_account_blocked(&self, owner: OwnerId) -> bool {
+        self.blocked_accounts.contains(&owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BookError;
+    use crate::order::{MarketOrderOptions, OrderSide};
+    use crate::risk::AccountExposure;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_kill_switch() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        let first = book
+            .add_limit_order_for(7, OrderSide::Sell, dec!(101.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order_for(8, OrderSide::Sell, dec!(101.00), dec!(3))
+            .expect("invalid order");
+        let second = book
+            .add_limit_order_for(7, OrderSide::Buy, dec!(99.00), dec!(2))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(98.00), dec!(4))
+            .expect("invalid order");
+        book.execute_market_order(OrderSide::Buy, dec!(1))
+            .expect("enough liquidity");
+        let stop = book
+            .add_stop_order_for(7, OrderSide::Sell, dec!(95.00), dec!(2))
+            .expect("valid stop");
+        book.add_stop_order_for(8, OrderSide::Buy, dec!(110.00), dec!(1))
+            .expect("valid stop");
+
+        let cancelled = book.kill_switch(7);
+        assert_eq!(cancelled, vec![first.order_id, second.order_id, stop]);
+        assert_eq!(
+            book.drain_events(),
+            vec![
+                BookEvent::OrderCancelled {
+                    order_id: second.order_id,
+                    owner: 7,
+                    side: OrderSide::Buy,
+                    price: dec!(99.00),
+                    quantity: dec!(2),
+                },
+                BookEvent::OrderCancelled {
+                    order_id: first.order_id,
+                    owner: 7,
+                    side: OrderSide::Sell,
+                    price: dec!(101.00),
+                    quantity: dec!(4),
+                },
+                BookEvent::OrderCancelled {
+                    order_id: stop,
+                    owner: 7,
+                    side: OrderSide::Sell,
+                    price: dec!(95.00),
+                    quantity: dec!(2),
+                },
+                BookEvent::AccountBlocked { owner: 7 },
+            ]
+        );
+
+        // Other accounts are untouched
+        assert_eq!(book.bid_levels(5), vec![(dec!(98.00), dec!(4))]);
+        assert_eq!(book.ask_levels(5), vec![(dec!(101.00), dec!(3))]);
+        assert_eq!(book.stop_orders().len(), 1);
+        assert_eq!(book.stop_orders()[0].owner, Some(8));
+        assert_eq!(book.account_exposure(7).open_orders, 0);
+        assert_eq!(
+            book.account_exposure(7),
+            AccountExposure {
+                position: dec!(-1),
+                ..AccountExposure::default()
+            }
+        );
+
+        // Every submission path is blocked
+        let blocked = Err(BookError::AccountBlocked(7));
+        assert_eq!(
+            book.add_limit_order_for(7, OrderSide::Buy, dec!(99.00), dec!(1)),
+            blocked
+        );
+        assert_eq!(
+            book.execute_market_order_for(
+                7,
+                OrderSide::Buy,
+                dec!(1),
+                MarketOrderOptions::default()
+            ),
+            blocked
+        );
+        assert_eq!(
+            book.execute_notional_market_order_for(7, OrderSide::Sell, dec!(98), dec!(1)),
+            blocked
+        );
+        assert_eq!(
+            book.add_stop_order_for(7, OrderSide::Sell, dec!(95.00), dec!(1)),
+            Err(BookError::AccountBlocked(7))
+        );
+        assert!(book.kill_switch(7).is_empty());
+        assert!(book.drain_events().is_empty());
+
+        assert!(book.reenable_account(7));
+        assert!(!book.reenable_account(7));
+        assert!(!book.is_account_blocked(7));
+        assert_eq!(
+            book.drain_events(),
+            vec![BookEvent::AccountReenabled { owner: 7 }]
+        );
+        let order = book
+            .add_limit_order_for(7, OrderSide::Buy, dec!(99.00), dec!(1))
+            .expect("account re-enabled");
+
+        // Cancels the owner asked for are not announced
+        book.cancel_limit_order(order.order_id)
+            .expect("order is resting");
+        assert!(book.drain_events().is_empty());
+    }
+}
@@ -1,9 +1,49 @@
 // in lib.rs
+pub mod allocation;
+pub mod auction;
+pub mod bands;
+pub mod batch;
+pub mod checksum;
+pub mod clock;
+pub mod error;
+pub mod event;
+pub mod fees;
+pub mod instrument;
+pub mod kill_switch;
 pub mod order;
 pub mod order_book;
+pub mod phase;
+pub mod position;
+pub mod preview;
+pub mod replica;
+pub mod risk;
+pub mod stop;
 pub mod ticks;
+pub mod volatility;
 
 // Re-export main types for easier use
-pub use order::{Fill, Order, OrderId, OrderSide, OrderType};
-pub use order_book::OrderBook;
-pub use ticks::Tick;
+pub use allocation::{
+    AllocationPipeline, AllocationStep, AllocationStrategy, Fifo, FifoPercentage, LeadMarketMaker,
+    Leveling, ProRata, TopOrder,
+};
+pub use auction::{AuctionEquilibrium, UncrossResult};
+pub use bands::{BandAction, PriceBand};
+pub use checksum::ChecksumFormat;
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use error::BookError;
+pub use event::BookEvent;
+pub use fees::{FeeCurrency, FeeRates, FeeSchedule};
+pub use instrument::InstrumentSpec;
+pub use order::{
+    ExecutionReport, Fill, LotRounding, MarketOrderOptions, Order, OrderId, OrderSide, OrderStatus,
+    OrderType, OwnerId,
+};
+pub use order_book::{OrderBook, Orders};
+pub use phase::TradingPhase;
+pub use position::{MarkPrice, Position, PositionKeeper};
+pub use preview::SweepPreview;
+pub use replica::{DeltaOutcome, LevelUpdate, ReplicaBook};
+pub use risk::{AccountExposure, RiskLimits, RiskViolation};
+pub use stop::StopOrder;
+pub use ticks::{Tick, TickMigration, TickPolicy, TickTable};
+pub use volatility::VolatilityBreaker;
@@ -1,3 +1,6 @@
+use crate::error::BookError;
+use crate::fees::FeeCurrency;
+
 use rust_decimal::Decimal;
 
 /// Unique identifier for orders. Implemented as a simple incrementing counter.
@@ -5,6 +8,10 @@ use rust_decimal::Decimal;
 /// and we maintain strict sequence.
 pub type OrderId = u64;
 
+/// Identifies the account or participant an order belongs to. Assigned by
+/// the caller, the book only uses it to tell participants apart.
+pub type OwnerId = u64;
+
 /// The type of order, determining how it will be processed in the book.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum OrderType {
@@ -28,12 +35,17 @@ pub enum OrderSide {
 /// the data structure.
 ///
 /// # Fields
-/// All fields are immutable after creation to maintain order integrity.
+/// `id`, `order_type` and `order_side` never change once the order is
+/// created. `owner` is set when the order is placed on an account's
+/// behalf, and `quantity` is what is left to fill: partial fills reduce
+/// it in place while the order keeps its place in the queue.
+#[derive(Clone, Debug)]
 pub struct Order {
-    pub id: OrderId,           // Unique identifier
-    pub quantity: Decimal,     // Size of order
-    pub order_type: OrderType, // Limit/Market
-    pub order_side: OrderSide, // Buy/Sell
+    pub id: OrderId,            // Unique identifier
+    pub quantity: Decimal,      // Size of order
+    pub order_type: OrderType,  // Limit/Market
+    pub order_side: OrderSide,  // Buy/Sell
+    pub owner: Option<OwnerId>, // None for anonymous orders
 }
 
 impl Order {
@@ -42,9 +54,9 @@ impl Order {
         quantity: Decimal,
         order_type: OrderType,
         order_side: OrderSide,
-    ) -> eyre::Result<Self> {
+    ) -> Result<Self, BookError> {
         if quantity <= Decimal::ZERO {
-            return Err(eyre::eyre!("Quantity must be positive"));
+            return Err(BookError::InvalidQuantity);
         }
 
         Ok(Self {
@@ -52,8 +64,14 @@ impl Order {
             quantity,
             order_type,
             order_side,
+            owner: None,
         })
     }
+
+    pub fn with_owner(mut self, owner: OwnerId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
 }
 
 /// Represents a match between two orders in the book.
@@ -66,6 +84,11 @@ impl Order {
 /// * `price` - The price at which the fill occurred
 /// * `taker_order_id` - The order that initiated the match (incoming order)
 /// * `maker_order_id` - The resting order that was matched against
+/// * `taker_side` - Whether the taker bought or sold, the maker did the opposite
+/// * `taker_owner` / `maker_owner` - Who submitted each order, if known
+/// * `taker_fee` / `maker_fee` - Fees charged to each party, zero without a
+///   fee schedule (see [`crate::FeeSchedule`]). A negative fee is a rebate.
+/// * `fee_currency` - Whether the fees are in quote or base currency
 ///
 /// # Terminology
 /// * Maker: The passive order already resting in the book
@@ -79,30 +102,122 @@ impl Order {
 /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
 ///
 /// // Add a resting sell order (maker)
-/// let (maker_id, _) = book.add_limit_order(
+/// let maker_id = book.add_limit_order(
 ///     OrderSide::Sell,
 ///     dec!(100.00),
 ///     dec!(10),
-/// ).expect("invalid order");
+/// ).expect("invalid order").order_id;
 ///
 /// // Add a buy order that crosses (taker)
-/// let (taker_id, fills) = book.add_limit_order(
+/// let report = book.add_limit_order(
 ///     OrderSide::Buy,
 ///     dec!(100.00),
 ///     dec!(5),
 /// ).expect("invalid order");
 ///
 /// // Examine the fill
-/// let fill = &fills[0];
+/// let fill = &report.fills[0];
 /// assert_eq!(fill.quantity, dec!(5));
 /// assert_eq!(fill.price, dec!(100.00));
 /// assert_eq!(fill.maker_order_id, maker_id);
-/// assert_eq!(fill.taker_order_id, taker_id);
+/// assert_eq!(fill.taker_order_id, report.order_id);
 /// # }
 /// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Fill {
     pub quantity: Decimal,
     pub price: Decimal,          // The price this fill occurred at
     pub taker_order_id: OrderId, // The incoming order
     pub maker_order_id: OrderId, // The resting order it matched with
+    pub taker_side: OrderSide,
+    pub taker_owner: Option<OwnerId>,
+    pub maker_owner: Option<OwnerId>,
+    pub taker_fee: Decimal,
+    pub maker_fee: Decimal,
+    pub fee_currency: FeeCurrency,
+}
+
+/// Execution options for market orders.
+///
+/// The default matches `execute_market_order`: fill completely at any
+/// price or reject.
+///
+/// # Fields
+/// * `allow_partial` - Fill what is available and report the remainder instead of rejecting
+/// * `max_slippage` - Furthest distance from the best opposite price at arrival the order may sweep to
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MarketOrderOptions {
+    pub allow_partial: bool,
+    pub max_slippage: Option<Decimal>, // Price distance, not a percentage
+}
+
+/// How the quantity of a notional market order's final partial fill is
+/// rounded to its lot size.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum LotRounding {
+    /// Round down, so the budget is never exceeded
+    #[default]
+    Down,
+    /// Round to the nearest lot, overspending by at most half a lot
+    Nearest,
+    /// Round up, spending the whole budget and at most one lot more
+    Up,
+}
+
+/// The state of an order once a submission call returns.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OrderStatus {
+    /// Resting in the book without any fills
+    New,
+    /// Resting in the book after some fills
+    PartiallyFilled,
+    /// Completely filled
+    Filled,
+    /// No longer working, the unfilled remainder was cancelled
+    /// (see `reject_reason`). Earlier fills still stand.
+    Cancelled,
+}
+
+/// The result of submitting an order, returned by every submission API.
+///
+/// # Fields
+/// * `order_id` - The id assigned to the order, also the `taker_order_id` of its fills
+/// * `side` - Buy or Sell
+/// * `order_type` - Limit or Market
+/// * `status` - State of the order after matching
+/// * `fills` - Fills generated by the order, in matching order
+/// * `filled_quantity` - Total quantity filled
+/// * `filled_notional` - Sum of price * quantity over the fills
+/// * `leaves_quantity` - Quantity resting in the book
+/// * `cancelled_quantity` - Quantity that neither filled nor rested
+/// * `unspent_notional` - For notional-sized orders, the budget that was not spent.
+///   Negative when lot rounding overspent it.
+/// * `reject_reason` - Why the remainder was cancelled, if it was
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{OrderBook, OrderSide, OrderStatus};
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+/// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(4)).unwrap();
+///
+/// let report = book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10)).unwrap();
+/// assert_eq!(report.status, OrderStatus::PartiallyFilled);
+/// assert_eq!(report.filled_quantity, dec!(4));
+/// assert_eq!(report.leaves_quantity, dec!(6));
+/// assert_eq!(report.fills[0].taker_order_id, report.order_id);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExecutionReport {
+    pub order_id: OrderId,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub status: OrderStatus,
+    pub fills: Vec<Fill>,
+    pub filled_quantity: Decimal,
+    pub filled_notional: Decimal,
+    pub leaves_quantity: Decimal,
+    pub cancelled_quantity: Decimal,
+    pub unspent_notional: Option<Decimal>, // None unless sized in quote currency
+    pub reject_reason: Option<BookError>,
 }
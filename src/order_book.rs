@@ -1,39 +1,259 @@
-use crate::order::{Fill, Order, OrderId, OrderSide, OrderType};
-use crate::ticks::Tick;
-
-use rust_decimal::Decimal;
-use std::collections::{BTreeMap, HashMap, VecDeque};
-
-// Orders structure with useful metadata
+use crate::allocation::{AllocationStrategy, Fifo};
+use crate::auction::{AuctionDepth, AuctionEquilibrium};
+use crate::bands::PriceBand;
+use crate::batch::BatchSchedule;
+use crate::clock::{Clock, SystemClock};
+use crate::error::BookError;
+use crate::event::BookEvent;
+use crate::fees::{FeeCurrency, FeeSchedule};
+use crate::instrument::InstrumentSpec;
+use crate::order::{
+    ExecutionReport, Fill, LotRounding, MarketOrderOptions, Order, OrderId, OrderSide, OrderStatus,
+    OrderType, OwnerId,
+};
+use crate::phase::TradingPhase;
+use crate::position::PositionKeeper;
+use crate::risk::{self, AccountExposure, RiskLimits};
+use crate::stop::StopOrder;
+use crate::ticks::{Tick, TickMigration, TickPolicy, TickTable};
+use crate::volatility::VolatilityBreaker;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+/// The orders resting at one price level, in time priority, with useful
+/// metadata. Allocation strategies read a level through this type.
 pub struct Orders {
-    orders: VecDeque<Order>,
-    total_volume: Decimal, // Cache of total volume at this tick
-    order_count: usize,    // Cache of number of orders
+    pub(crate) orders: VecDeque<Order>,
+    pub(crate) total_volume: Decimal, // Cache of total volume at this tick
+    pub(crate) order_count: usize,    // Cache of number of orders
+    pub(crate) top_order: Option<OrderId>, // The order that set this price as the best
 }
 
 impl Orders {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             orders: VecDeque::new(),
             total_volume: Decimal::ZERO,
             order_count: 0,
+            top_order: None,
         }
     }
 
-    fn add_order(&mut self, order: Order) {
+    /// Resting orders, earliest first
+    pub fn orders(&self) -> &VecDeque<Order> {
+        &self.orders
+    }
+
+    /// Total resting quantity at this level
+    pub fn total_volume(&self) -> Decimal {
+        self.total_volume
+    }
+
+    /// The order that first bettered the market at this price, if it is
+    /// still resting here
+    pub fn top_order(&self) -> Option<OrderId> {
+        self.top_order
+            .filter(|top| self.orders.iter().any(|order| order.id == *top))
+    }
+
+    pub(crate) fn add_order(&mut self, order: Order) {
         self.total_volume += order.quantity;
         self.order_count += 1;
         self.orders.push_back(order);
     }
 
-    fn remove_order(&mut self, order_id: OrderId) -> eyre::Result<Order> {
+    fn remove_order(&mut self, order_id: OrderId) -> Result<Order, BookError> {
         if let Some(pos) = self.orders.iter().position(|order| order.id == order_id) {
             let order = self.orders.remove(pos).unwrap();
             self.total_volume -= order.quantity;
             self.order_count -= 1;
             Ok(order)
         } else {
-            Err(eyre::eyre!("Order not found in tick level"))
+            Err(BookError::OrderNotFound(order_id))
+        }
+    }
+
+    // Merge another level's queue into this one. Order ids are assigned in
+    // arrival sequence, so ordering by id keeps the relative time priority
+    // of both queues.
+    fn merge(&mut self, other: Orders) {
+        self.total_volume += other.total_volume;
+        self.order_count += other.order_count;
+        self.top_order = self.top_order.or(other.top_order);
+        self.orders.extend(other.orders);
+        self.orders.make_contiguous().sort_by_key(|order| order.id);
+    }
+}
+
+// The fills an incoming order produces at one price level
+struct LevelMatch {
+    tick: Tick,
+    allocations: Vec<Decimal>, // Quantity taken from each order, in queue order
+}
+
+// Whether `price` is further than `limit` in the direction an order on
+// `side` trades
+fn beyond(side: OrderSide, price: Decimal, limit: Decimal) -> bool {
+    match side {
+        OrderSide::Buy => price > limit,
+        OrderSide::Sell => price < limit,
+    }
+}
+
+// How much an incoming order may take from the book
+#[derive(Copy, Clone)]
+pub(crate) enum MatchBudget {
+    Quantity(Decimal),
+    // Quote currency to spend, with quantities rounded to `lot_size`
+    Notional {
+        amount: Decimal,
+        lot_size: Decimal,
+        rounding: LotRounding,
+    },
+}
+
+// Why matching stopped
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(crate) enum MatchStop {
+    BudgetUsed,          // Got everything the order asked for
+    PriceLimit,          // Next level no longer crosses the limit or protection price
+    BookExhausted,       // No levels left on the opposite side
+    PriceBand(Decimal),  // Next level is beyond this price band limit
+    Volatility(Decimal), // Next level is beyond this volatility breaker limit
+}
+
+impl MatchStop {
+    // Why a market order that stopped here did not fill completely
+    pub(crate) fn cancel_reason(self) -> BookError {
+        match self {
+            MatchStop::PriceLimit => BookError::ProtectionPriceReached,
+            MatchStop::PriceBand(_) => BookError::PriceBandReached,
+            MatchStop::Volatility(_) => BookError::VolatilityInterruption,
+            _ => BookError::InsufficientLiquidity,
+        }
+    }
+}
+
+// Outcome of matching an incoming order, computed before the book is touched
+pub(crate) struct MatchPlan {
+    taker_order_id: OrderId,
+    pub(crate) fills: Vec<Fill>,
+    levels: Vec<LevelMatch>,
+    pub(crate) filled_quantity: Decimal,
+    pub(crate) notional: Decimal, // Sum of price * quantity over the fills
+    pub(crate) stop: MatchStop,
+}
+
+impl MatchPlan {
+    // A plan without fills, for an order that matches nothing
+    fn new(taker_order_id: OrderId) -> Self {
+        Self {
+            taker_order_id,
+            fills: Vec::new(),
+            levels: Vec::new(),
+            filled_quantity: Decimal::ZERO,
+            notional: Decimal::ZERO,
+            stop: MatchStop::BookExhausted,
+        }
+    }
+
+    // Price levels the plan takes volume from
+    pub(crate) fn levels_consumed(&self) -> usize {
+        self.levels.len()
+    }
+
+    fn into_report(self, side: OrderSide, order_type: OrderType) -> ExecutionReport {
+        ExecutionReport {
+            order_id: self.taker_order_id,
+            side,
+            order_type,
+            status: OrderStatus::Filled,
+            fills: self.fills,
+            filled_quantity: self.filled_quantity,
+            filled_notional: self.notional,
+            leaves_quantity: Decimal::ZERO,
+            cancelled_quantity: Decimal::ZERO,
+            unspent_notional: None,
+            reject_reason: None,
+        }
+    }
+
+    // Limit orders rest whatever did not fill
+    fn into_limit_report(self, side: OrderSide, quantity: Decimal) -> ExecutionReport {
+        let leaves_quantity = quantity - self.filled_quantity;
+        let status = if leaves_quantity == Decimal::ZERO {
+            OrderStatus::Filled
+        } else if self.filled_quantity > Decimal::ZERO {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::New
+        };
+
+        ExecutionReport {
+            status,
+            leaves_quantity,
+            ..self.into_report(side, OrderType::Limit)
+        }
+    }
+
+    // Orders that must fill completely are rejected when the plan falls short
+    fn require_complete(
+        &self,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<(), BookError> {
+        if options.allow_partial || self.filled_quantity == quantity {
+            return Ok(());
+        }
+
+        Err(match self.stop {
+            MatchStop::PriceBand(_) => BookError::PriceBandReached,
+            MatchStop::Volatility(_) => BookError::VolatilityInterruption,
+            _ => BookError::ProtectionPriceReached,
+        })
+    }
+
+    // Market orders never rest, whatever did not fill is cancelled
+    fn into_market_report(self, side: OrderSide, quantity: Decimal) -> ExecutionReport {
+        let cancelled_quantity = quantity - self.filled_quantity;
+        if cancelled_quantity == Decimal::ZERO {
+            return self.into_report(side, OrderType::Market);
+        }
+
+        let reject_reason = self.stop.cancel_reason();
+
+        ExecutionReport {
+            status: OrderStatus::Cancelled,
+            cancelled_quantity,
+            reject_reason: Some(reject_reason),
+            ..self.into_report(side, OrderType::Market)
+        }
+    }
+
+    fn into_notional_report(self, side: OrderSide, notional: Decimal) -> ExecutionReport {
+        let unspent_notional = notional - self.notional;
+        let reject_reason = if self.fills.is_empty() && self.stop == MatchStop::BudgetUsed {
+            Some(BookError::NotionalBelowLot)
+        } else if self.stop == MatchStop::BookExhausted && unspent_notional > Decimal::ZERO {
+            Some(BookError::InsufficientLiquidity)
+        } else if let MatchStop::PriceBand(_) = self.stop {
+            Some(BookError::PriceBandReached)
+        } else if let MatchStop::Volatility(_) = self.stop {
+            Some(BookError::VolatilityInterruption)
+        } else {
+            None
+        };
+
+        ExecutionReport {
+            status: match reject_reason {
+                Some(_) => OrderStatus::Cancelled,
+                None => OrderStatus::Filled,
+            },
+            unspent_notional: Some(unspent_notional),
+            reject_reason,
+            ..self.into_report(side, OrderType::Market)
         }
     }
 }
@@ -61,21 +281,21 @@ impl Orders {
 /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
 ///
 /// // Add a limit sell order
-/// let (sell_id, _) = book.add_limit_order(
+/// let sell = book.add_limit_order(
 ///     OrderSide::Sell,
 ///     dec!(100.00),
 ///     dec!(10),
 /// ).expect("invalid order");
 ///
 /// // Add a matching buy order
-/// let (buy_id, fills) = book.add_limit_order(
+/// let buy = book.add_limit_order(
 ///     OrderSide::Buy,
 ///     dec!(100.00),
 ///     dec!(5),
 /// ).expect("invalid order");
 /// ```
 pub struct OrderBook {
-    pub(crate) tick_size: Decimal, // e.g., 0.01
+    pub(crate) spec: InstrumentSpec, // Tick size and quantity constraints
     pub(crate) bids: BTreeMap<Tick, Orders>,
     pub(crate) asks: BTreeMap<Tick, Orders>,
     pub(crate) next_id: OrderId, // Starts at 0 and increments so there is never a collision
@@ -84,27 +304,128 @@ pub struct OrderBook {
     // Add these to track total liquidity
     pub(crate) total_bid_volume: Decimal,
     pub(crate) total_ask_volume: Decimal,
+    pub(crate) tick_policy: TickPolicy, // How off-tick limit prices are handled
+    pub(crate) lot_rounding: LotRounding, // How notional market orders round their last fill
+    pub(crate) allocation: Box<dyn AllocationStrategy>, // How a level's volume is shared out
+    pub(crate) phase: TradingPhase,
+    pub(crate) reference_price: Option<Decimal>, // Last trade price unless set explicitly
+    pub(crate) indicative: Option<AuctionEquilibrium>, // Published during auctions
+    pub(crate) auction_depth: Option<AuctionDepth>, // Running volumes the indicative comes from
+    pub(crate) events: Vec<BookEvent>,           // Waiting for drain_events
+    pub(crate) price_band: Option<PriceBand>,
+    pub(crate) volatility_breaker: Option<VolatilityBreaker>,
+    pub(crate) trade_history: VecDeque<(Duration, Decimal)>, // Fill times and prices in the window
+    pub(crate) volatility_auction_ends_at: Option<Duration>,
+    pub(crate) fee_schedule: Option<FeeSchedule>,
+    pub(crate) owner_volumes: HashMap<OwnerId, VecDeque<(Duration, Decimal)>>, // Notional traded for fee tiers
+    pub(crate) risk_limits: HashMap<OwnerId, RiskLimits>,
+    pub(crate) accounts: HashMap<OwnerId, AccountExposure>, // Working quantity per owner
+    pub(crate) positions: PositionKeeper,                   // Filled quantity and PnL per owner
+    pub(crate) blocked_accounts: HashSet<OwnerId>,          // Stopped by the kill switch
+    pub(crate) stop_orders: Vec<StopOrder>,                 // Waiting to trigger, in arrival order
+    pub(crate) clock: Box<dyn Clock>,
+    pub(crate) batch: Option<BatchSchedule>, // Set in frequent batch auction mode
 }
 
 impl OrderBook {
-    pub fn new(tick_size: Decimal) -> eyre::Result<Self> {
-        if tick_size <= Decimal::ZERO {
-            return Err(eyre::eyre!("Tick size must be positive"));
-        }
+    pub fn new(tick_size: Decimal) -> Result<Self, BookError> {
+        Self::from_spec(InstrumentSpec::new(tick_size))
+    }
+
+    /// Create a book for an instrument with lot size, quantity and notional
+    /// constraints. Every submission path rejects orders that break them.
+    ///
+    /// Fails if the spec is inconsistent, see [`InstrumentSpec::validate`].
+    pub fn from_spec(spec: InstrumentSpec) -> Result<Self, BookError> {
+        spec.validate()?;
 
         Ok(Self {
-            tick_size,
+            spec,
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
             next_id: 0, // Start at 0
             order_lookup: HashMap::new(),
             total_bid_volume: Decimal::ZERO,
             total_ask_volume: Decimal::ZERO,
+            tick_policy: TickPolicy::default(),
+            lot_rounding: LotRounding::default(),
+            allocation: Box::new(Fifo),
+            phase: TradingPhase::Continuous,
+            reference_price: None,
+            indicative: None,
+            auction_depth: None,
+            events: Vec::new(),
+            price_band: None,
+            volatility_breaker: None,
+            trade_history: VecDeque::new(),
+            volatility_auction_ends_at: None,
+            fee_schedule: None,
+            owner_volumes: HashMap::new(),
+            risk_limits: HashMap::new(),
+            accounts: HashMap::new(),
+            positions: PositionKeeper::new(),
+            blocked_accounts: HashSet::new(),
+            stop_orders: Vec::new(),
+            clock: Box::new(SystemClock),
+            batch: None,
         })
     }
 
+    /// Set how limit prices that are not on a tick are handled.
+    ///
+    /// Defaults to [`TickPolicy::Nearest`].
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{BookError, OrderBook, OrderSide, TickPolicy};
+    /// let mut book = OrderBook::new(dec!(0.01))
+    ///     .unwrap()
+    ///     .with_tick_policy(TickPolicy::Reject);
+    ///
+    /// let result = book.add_limit_order(OrderSide::Buy, dec!(100.017), dec!(1));
+    /// assert_eq!(result, Err(BookError::PriceNotAligned));
+    /// ```
+    pub fn with_tick_policy(mut self, tick_policy: TickPolicy) -> Self {
+        self.tick_policy = tick_policy;
+        self
+    }
+
+    /// Set how the final partial fill of a notional market order is rounded
+    /// to its lot size.
+    ///
+    /// Defaults to [`LotRounding::Down`], which never exceeds the budget.
+    pub fn with_lot_rounding(mut self, lot_rounding: LotRounding) -> Self {
+        self.lot_rounding = lot_rounding;
+        self
+    }
+
+    /// Set how incoming quantity is split across the orders resting at a
+    /// price level.
+    ///
+    /// Defaults to [`Fifo`] price-time priority. See [`crate::ProRata`] for
+    /// pro-rata matching.
+    pub fn with_allocation(mut self, allocation: impl AllocationStrategy + 'static) -> Self {
+        self.allocation = Box::new(allocation);
+        self
+    }
+
+    /// Set the clock used for anything the book schedules.
+    ///
+    /// Defaults to [`SystemClock`]. Use a [`crate::ManualClock`] to drive the
+    /// book through simulated time.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The instrument constraints this book enforces
+    pub fn spec(&self) -> &InstrumentSpec {
+        &self.spec
+    }
+
     // OrderId Incrementer
-    fn next_order_id(&mut self) -> OrderId {
+    pub(crate) fn next_order_id(&mut self) -> OrderId {
         let id = self.next_id;
         self.next_id += 1;
         id
@@ -118,9 +439,10 @@ impl OrderBook {
     /// * `quantity` - Size of the order
     ///
     /// # Returns
-    /// Returns a tuple of:
-    /// * `OrderId` - Unique identifier for the order (always generated, even for immediate fills)
-    /// * `Vec<Fill>` - Any fills that occurred during matching
+    /// Returns an [`ExecutionReport`] containing:
+    /// * `order_id` - Unique identifier for the order (always generated, even for immediate fills)
+    /// * `fills` - Any fills that occurred during matching
+    /// * `leaves_quantity` - The quantity added to the book
     ///
     /// # Matching Behavior
     /// 1. For Buy Orders:
@@ -138,32 +460,38 @@ impl OrderBook {
     /// Within each price level, orders are matched in time priority (FIFO).
     /// An OrderId is always generated and returned, even if the order fills immediately.
     ///
+    /// The price is first normalized to a tick according to the book's
+    /// [`TickPolicy`], and that same price is used both for matching and
+    /// for resting the remainder.
+    ///
     /// # Examples
     /// ```
     /// # use rust_decimal_macros::dec;
-    /// # use limitbook::{OrderBook, OrderSide};
+    /// # use limitbook::{OrderBook, OrderSide, OrderStatus};
     /// # fn main() {
     /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
     ///
     /// // Add a resting limit sell order
-    /// let (sell_id, fills) = book.add_limit_order(
+    /// let sell = book.add_limit_order(
     ///     OrderSide::Sell,
     ///     dec!(100.00),
     ///     dec!(10),
     /// ).expect("invalid order");
-    /// assert!(fills.is_empty());  // No fills, order rests
+    /// assert!(sell.fills.is_empty());  // No fills, order rests
+    /// assert_eq!(sell.status, OrderStatus::New);
     ///
     /// // Add a limit buy that crosses the book (immediate execution)
-    /// let (buy_id, fills) = book.add_limit_order(
+    /// let buy = book.add_limit_order(
     ///     OrderSide::Buy,
     ///     dec!(100.00),
     ///     dec!(5),
     /// ).expect("invalid order");
-    /// assert_eq!(fills.len(), 1);  // One fill occurred
-    /// assert_eq!(fills[0].quantity, dec!(5));
-    /// assert_eq!(fills[0].price, dec!(100.00));
-    /// assert_eq!(fills[0].maker_order_id, sell_id);
-    /// assert_eq!(fills[0].taker_order_id, buy_id);
+    /// assert_eq!(buy.status, OrderStatus::Filled);
+    /// assert_eq!(buy.fills.len(), 1);  // One fill occurred
+    /// assert_eq!(buy.fills[0].quantity, dec!(5));
+    /// assert_eq!(buy.fills[0].price, dec!(100.00));
+    /// assert_eq!(buy.fills[0].maker_order_id, sell.order_id);
+    /// assert_eq!(buy.fills[0].taker_order_id, buy.order_id);
     /// # }
     /// ```
     pub fn add_limit_order(
@@ -171,148 +499,135 @@ impl OrderBook {
         order_side: OrderSide,
         price: Decimal,
         quantity: Decimal,
-    ) -> eyre::Result<(OrderId, Vec<Fill>)> {
-        if price <= Decimal::ZERO {
-            return Err(eyre::eyre!("Price must be positive"));
-        }
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_limit_order(None, order_side, price, quantity)
+    }
 
-        if quantity <= Decimal::ZERO {
-            return Err(eyre::eyre!("Quantity must be positive"));
+    /// Add a limit order on behalf of `owner`.
+    ///
+    /// Behaves exactly like `add_limit_order`. The owner stays attached to
+    /// the resting order, for allocation strategies that treat some
+    /// participants differently (see [`crate::LeadMarketMaker`]).
+    pub fn add_limit_order_for(
+        &mut self,
+        owner: OwnerId,
+        order_side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_limit_order(Some(owner), order_side, price, quantity)
+    }
+
+    fn submit_limit_order(
+        &mut self,
+        owner: Option<OwnerId>,
+        order_side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        let tick = self.validate_limit_order(order_side, price, quantity)?;
+        if let Some(owner) = owner {
+            let price = tick.level();
+            self.check_risk(
+                owner,
+                order_side,
+                quantity,
+                price * quantity,
+                Some(price),
+                true,
+            )?;
         }
 
         let order_id = self.next_order_id();
-        let mut fills = Vec::new();
-        let mut remaining_quantity = quantity;
-
-        // Check if this order crosses the book
-        match order_side {
-            OrderSide::Buy => {
-                while remaining_quantity > Decimal::ZERO {
-                    let mut entry = match self.asks.first_entry() {
-                        Some(entry) => entry,
-                        None => break, // No more asks to match against
-                    };
-
-                    if price < entry.key().level() {
-                        break; // Price no longer crosses
-                    }
-
-                    // Get the price level before mutable borrow
-                    let ask_price = entry.key().level();
-                    let orders = entry.get_mut();
-
-                    // Match against orders at this level
-                    while remaining_quantity > Decimal::ZERO && !orders.orders.is_empty() {
-                        let resting_order = orders
-                            .orders
-                            .front_mut()
-                            .expect("Orders empty but should have orders");
-
-                        let fill_quantity = remaining_quantity.min(resting_order.quantity);
-
-                        fills.push(Fill {
-                            quantity: fill_quantity,
-                            price: ask_price,
-                            taker_order_id: order_id,
-                            maker_order_id: resting_order.id,
-                        });
-
-                        remaining_quantity -= fill_quantity;
-                        orders.total_volume -= fill_quantity;
-                        self.total_ask_volume -= fill_quantity;
-
-                        if fill_quantity == resting_order.quantity {
-                            let removed_order = orders.orders.pop_front().unwrap();
-                            orders.order_count -= 1;
-                            self.order_lookup.remove(&removed_order.id);
-                        }
-                    }
-
-                    // Remove empty price level
-                    if orders.order_count == 0 {
-                        entry.remove();
-                    }
-                }
-            }
-            OrderSide::Sell => {
-                while remaining_quantity > Decimal::ZERO {
-                    let mut entry = match self.bids.last_entry() {
-                        Some(entry) => entry,
-                        None => break, // No more bids to match against
-                    };
-
-                    if price > entry.key().level() {
-                        break; // Price no longer crosses
-                    }
-
-                    // Get the price level before mutable borrow
-                    let bid_price = entry.key().level();
-                    let orders = entry.get_mut();
-
-                    // Match against orders at this level
-                    while remaining_quantity > Decimal::ZERO && !orders.orders.is_empty() {
-                        let resting_order = orders
-                            .orders
-                            .front_mut()
-                            .expect("Orders empty but should have orders");
-
-                        let fill_quantity = remaining_quantity.min(resting_order.quantity);
-
-                        fills.push(Fill {
-                            quantity: fill_quantity,
-                            price: bid_price,
-                            taker_order_id: order_id,
-                            maker_order_id: resting_order.id,
-                        });
-
-                        remaining_quantity -= fill_quantity;
-                        orders.total_volume -= fill_quantity;
-                        self.total_bid_volume -= fill_quantity;
-
-                        if fill_quantity == resting_order.quantity {
-                            let removed_order = orders.orders.pop_front().unwrap();
-                            orders.order_count -= 1;
-                            self.order_lookup.remove(&removed_order.id);
-                        }
-                    }
 
-                    // Remove empty price level
-                    if orders.order_count == 0 {
-                        entry.remove();
-                    }
-                }
-            }
-        }
+        let plan = self.plan_limit_order(order_id, owner, order_side, &tick, quantity);
+        self.apply_match(order_side, &plan);
+        self.interrupt_trading(order_side, plan.stop); // The remainder rests in the new phase
+        let remaining_quantity = quantity - plan.filled_quantity;
 
         // If we have remaining quantity, add it to the book
         if remaining_quantity > Decimal::ZERO {
-            let tick = Tick::new(price, self.tick_size).expect("invalid tick");
-            match order_side {
+            // An order that betters its side of the market becomes the
+            // level's top order
+            let improves = match order_side {
+                OrderSide::Buy => self.best_bid().is_none_or(|bid| tick.level() > bid),
+                OrderSide::Sell => self.best_ask().is_none_or(|ask| tick.level() < ask),
+            };
+
+            let book_side = match order_side {
                 OrderSide::Buy => {
                     self.total_bid_volume += remaining_quantity;
-                    self.bids
-                        .entry(tick.clone())
-                        .or_insert_with(Orders::new)
-                        .add_order(
-                            Order::new(order_id, remaining_quantity, OrderType::Limit, order_side)
-                                .expect("invalid order"),
-                        );
+                    &mut self.bids
                 }
                 OrderSide::Sell => {
                     self.total_ask_volume += remaining_quantity;
-                    self.asks
-                        .entry(tick.clone())
-                        .or_insert_with(Orders::new)
-                        .add_order(
-                            Order::new(order_id, remaining_quantity, OrderType::Limit, order_side)
-                                .expect("invalid order"),
-                        );
+                    &mut self.asks
                 }
+            };
+            let orders = book_side.entry(tick.clone()).or_insert_with(Orders::new);
+            let mut order = Order::new(order_id, remaining_quantity, OrderType::Limit, order_side)
+                .expect("invalid order");
+            order.owner = owner;
+            risk::track_resting(&mut self.accounts, &order);
+            orders.add_order(order);
+            if improves {
+                orders.top_order = Some(order_id);
             }
-            self.order_lookup.insert(order_id, (order_side, tick));
+            self.order_lookup
+                .insert(order_id, (order_side, tick.clone()));
+            self.on_auction_event(order_side, &tick, remaining_quantity);
         }
 
-        Ok((order_id, fills))
+        self.trigger_stops();
+        Ok(plan.into_limit_report(order_side, quantity))
+    }
+
+    // Match whatever crosses the book at the normalized price. In phases
+    // where orders only accumulate nothing matches.
+    fn plan_limit_order(
+        &self,
+        taker_order_id: OrderId,
+        taker_owner: Option<OwnerId>,
+        order_side: OrderSide,
+        tick: &Tick,
+        quantity: Decimal,
+    ) -> MatchPlan {
+        if !self.phase.matches_on_arrival() {
+            return MatchPlan::new(taker_order_id);
+        }
+
+        self.plan_match(
+            taker_order_id,
+            taker_owner,
+            order_side,
+            Some(tick.level()),
+            MatchBudget::Quantity(quantity),
+        )
+    }
+
+    // Validate a limit order and return the tick it matches and rests at
+    fn validate_limit_order(
+        &self,
+        order_side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<Tick, BookError> {
+        self.require_phase(TradingPhase::accepts_limit_orders)?;
+
+        if price <= Decimal::ZERO {
+            return Err(BookError::InvalidPrice);
+        }
+
+        if quantity <= Decimal::ZERO {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        let tick = Tick::for_order(price, &self.spec.tick_table, order_side, self.tick_policy)?;
+        let tick = self.apply_price_band(order_side, tick)?;
+        self.spec.check_quantity(quantity)?;
+        self.spec.check_notional(tick.level() * quantity)?;
+
+        Ok(tick)
     }
 
     /// Cancel an existing limit order.
@@ -344,11 +659,11 @@ impl OrderBook {
     /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
     ///
     /// // Add an order
-    /// let (order_id, _) = book.add_limit_order(
+    /// let order_id = book.add_limit_order(
     ///     OrderSide::Sell,
     ///     dec!(100.00),
     ///     dec!(10),
-    /// ).expect("invalid order");
+    /// ).expect("invalid order").order_id;
     ///
     /// // Cancel it
     /// book.cancel_limit_order(order_id)?;
@@ -358,12 +673,12 @@ impl OrderBook {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn cancel_limit_order(&mut self, order_id: OrderId) -> eyre::Result<()> {
+    pub fn cancel_limit_order(&mut self, order_id: OrderId) -> Result<(), BookError> {
         // Get the side and tick from our lookup
         let (side, tick) = self
             .order_lookup
             .get(&order_id)
-            .ok_or_else(|| eyre::eyre!("Order not found"))?;
+            .ok_or(BookError::OrderNotFound(order_id))?;
 
         // Get the appropriate book side (bids or asks)
         let book_side = match side {
@@ -374,15 +689,17 @@ impl OrderBook {
         // Get the orders at this tick level
         let orders = book_side
             .get_mut(tick)
-            .ok_or_else(|| eyre::eyre!("Tick level not found"))?;
+            .ok_or(BookError::TickLevelNotFound)?;
 
         // Get the removed order so we know its quantity
-        let removed_order = orders.remove_order(order_id)?;
+        let mut removed_order = orders.remove_order(order_id)?;
+        let cancelled_quantity = std::mem::take(&mut removed_order.quantity);
+        risk::release_resting(&mut self.accounts, &removed_order, cancelled_quantity);
 
         // Update total volume
         match side {
-            OrderSide::Buy => self.total_bid_volume -= removed_order.quantity,
-            OrderSide::Sell => self.total_ask_volume -= removed_order.quantity,
+            OrderSide::Buy => self.total_bid_volume -= cancelled_quantity,
+            OrderSide::Sell => self.total_ask_volume -= cancelled_quantity,
         }
 
         // If no orders left at this tick, remove the tick level
@@ -390,12 +707,143 @@ impl OrderBook {
             book_side.remove(tick);
         }
 
+        let (side, tick) = (*side, tick.clone());
+
         // Remove from lookup
         self.order_lookup.remove(&order_id);
 
+        self.on_auction_event(side, &tick, -cancelled_quantity);
         Ok(())
     }
 
+    /// Move a live book to a new tick size, re-keying every resting level.
+    ///
+    /// Levels already on a tick of the new table keep their price. Levels
+    /// that are not are handled according to `migration`. Where several old
+    /// levels map to the same new price they are merged, and orders keep
+    /// their relative time priority (earlier orders first).
+    ///
+    /// Returns the ids of the orders cancelled by the change, in ascending
+    /// order. Under `TickMigration::Round` a buy below the lowest tick has
+    /// nothing to round down to and is cancelled.
+    ///
+    /// # Errors
+    /// `BookError::PriceNotAligned` under `TickMigration::Reject` if any
+    /// resting order is misaligned, or an invalid tick table error. The book
+    /// is unchanged when an error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide, TickMigration, TickTable};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// let first = book.add_limit_order(OrderSide::Buy, dec!(100.03), dec!(10)).unwrap().order_id;
+    /// let second = book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(5)).unwrap().order_id;
+    ///
+    /// let cancelled = book
+    ///     .change_tick_size(TickTable::fixed(dec!(0.05)), TickMigration::Round)
+    ///     .unwrap();
+    /// assert!(cancelled.is_empty());
+    ///
+    /// // 100.03 rounds down into the 100.00 level and keeps its earlier priority
+    /// assert_eq!(book.bid_levels(5), vec![(dec!(100.00), dec!(15))]);
+    /// let fills = book.execute_market_order(OrderSide::Sell, dec!(12)).unwrap().fills;
+    /// assert_eq!(fills[0].maker_order_id, first);
+    /// assert_eq!(fills[1].maker_order_id, second);
+    /// ```
+    pub fn change_tick_size(
+        &mut self,
+        tick_table: TickTable,
+        migration: TickMigration,
+    ) -> Result<Vec<OrderId>, BookError> {
+        tick_table.validate()?;
+
+        // Work out every level's new tick before touching the book, so a
+        // rejected change leaves it as it was. None cancels the level.
+        let bid_ticks = Self::migrated_ticks(&self.bids, OrderSide::Buy, &tick_table, migration)?;
+        let ask_ticks = Self::migrated_ticks(&self.asks, OrderSide::Sell, &tick_table, migration)?;
+
+        let mut cancelled = Vec::new();
+        self.migrate_side(OrderSide::Buy, bid_ticks, &mut cancelled);
+        self.migrate_side(OrderSide::Sell, ask_ticks, &mut cancelled);
+        self.spec.tick_table = tick_table;
+        self.refresh_indicative();
+
+        cancelled.sort_unstable();
+        Ok(cancelled)
+    }
+
+    // The new tick of every level on one side, in key order
+    fn migrated_ticks(
+        levels: &BTreeMap<Tick, Orders>,
+        side: OrderSide,
+        tick_table: &TickTable,
+        migration: TickMigration,
+    ) -> Result<Vec<Option<Tick>>, BookError> {
+        levels
+            .keys()
+            .map(|tick| {
+                let price = tick.level();
+                if tick_table.is_aligned(price) {
+                    return Tick::in_table(price, tick_table).map(Some);
+                }
+
+                match migration {
+                    TickMigration::Reject => Err(BookError::PriceNotAligned),
+                    TickMigration::Cancel => Ok(None),
+                    TickMigration::Round => {
+                        Ok(Tick::for_order(price, tick_table, side, TickPolicy::Conservative).ok())
+                    }
+                }
+            })
+            .collect()
+    }
+
+    // Re-key one side of the book, merging levels that collide. Ticks
+    // compare by price alone, so a level rounded onto a band boundary joins
+    // the level already there.
+    fn migrate_side(
+        &mut self,
+        side: OrderSide,
+        new_ticks: Vec<Option<Tick>>,
+        cancelled: &mut Vec<OrderId>,
+    ) {
+        let book_side = match side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let levels = std::mem::take(book_side);
+        let mut cancelled_volume = Decimal::ZERO;
+
+        for ((_, orders), new_tick) in levels.into_iter().zip(new_ticks) {
+            match new_tick {
+                Some(tick) => {
+                    for order in &orders.orders {
+                        self.order_lookup.insert(order.id, (side, tick.clone()));
+                    }
+                    book_side
+                        .entry(tick)
+                        .or_insert_with(Orders::new)
+                        .merge(orders);
+                }
+                None => {
+                    cancelled_volume += orders.total_volume;
+                    for mut order in orders.orders {
+                        self.order_lookup.remove(&order.id);
+                        cancelled.push(order.id);
+                        let quantity = std::mem::take(&mut order.quantity);
+                        risk::release_resting(&mut self.accounts, &order, quantity);
+                    }
+                }
+            }
+        }
+
+        match side {
+            OrderSide::Buy => self.total_bid_volume -= cancelled_volume,
+            OrderSide::Sell => self.total_ask_volume -= cancelled_volume,
+        }
+    }
+
     // Market Order Matching Logic
     //
     // Price-Time Priority is maintained as follows:
@@ -403,13 +851,13 @@ impl OrderBook {
     // 1. Price Priority:
     //    - Market Buy orders match against asks in ascending price order (lowest ask first)
     //    - Market Sell orders match against bids in descending price order (highest bid first)
-    //    This is achieved using BTreeMap's ordered iteration (first_entry/last_entry)
+    //    This is achieved using BTreeMap's ordered iteration (see taker_levels)
     //
     // 2. Time Priority:
     //    - Within each price level, orders are stored in a VecDeque
     //    - Orders are matched in FIFO order (front to back)
     //    - New orders are always added to the back (push_back)
-    //    - Matches always take from the front (pop_front)
+    //    - Matches always take from the front, filled orders are removed
     //
     // Example:
     // For a market buy order of 100 units when the ask book looks like:
@@ -426,82 +874,519 @@ impl OrderBook {
         &mut self,
         side: OrderSide,
         quantity: Decimal,
-    ) -> eyre::Result<Vec<Fill>> {
+    ) -> Result<ExecutionReport, BookError> {
+        self.execute_market_order_with(side, quantity, MarketOrderOptions::default())
+    }
+
+    /// Execute a market order with partial-fill and price protection options.
+    ///
+    /// # Arguments
+    /// * `side` - Buy or Sell
+    /// * `quantity` - Size of the order
+    /// * `options` - See [`MarketOrderOptions`]
+    ///
+    /// # Behavior
+    /// - With `max_slippage` set, the order only sweeps levels within that
+    ///   distance of the best opposite price at arrival (the protection price)
+    /// - With `allow_partial` set, the order fills what is available up to
+    ///   the protection price and the rest is cancelled, reported as
+    ///   `cancelled_quantity` with the reason in `reject_reason`
+    /// - Without `allow_partial`, the order is rejected without touching the
+    ///   book unless it can fill completely
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{MarketOrderOptions, OrderBook, OrderSide, OrderStatus};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.50), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(105.00), dec!(10)).unwrap();
+    ///
+    /// let options = MarketOrderOptions {
+    ///     allow_partial: true,
+    ///     max_slippage: Some(dec!(1.00)), // Never pay more than 101.00
+    /// };
+    /// let report = book.execute_market_order_with(OrderSide::Buy, dec!(25), options).unwrap();
+    /// assert_eq!(report.status, OrderStatus::Cancelled);
+    /// assert_eq!(report.filled_quantity, dec!(20));
+    /// assert_eq!(report.cancelled_quantity, dec!(5));
+    /// assert_eq!(book.best_ask(), Some(dec!(105.00)));
+    /// ```
+    pub fn execute_market_order_with(
+        &mut self,
+        side: OrderSide,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_market_order(None, side, quantity, options)
+    }
+
+    /// Execute a market order on behalf of `owner`.
+    ///
+    /// Behaves exactly like `execute_market_order_with`, with the owner
+    /// reported on the fills and charged the fees of their tier.
+    pub fn execute_market_order_for(
+        &mut self,
+        owner: OwnerId,
+        side: OrderSide,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_market_order(Some(owner), side, quantity, options)
+    }
+
+    fn submit_market_order(
+        &mut self,
+        owner: Option<OwnerId>,
+        side: OrderSide,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<ExecutionReport, BookError> {
+        let report = self.match_market_order(owner, side, quantity, options)?;
+        self.trigger_stops();
+        Ok(report)
+    }
+
+    // Execute a market order without triggering stops, so triggered stops
+    // can run through here one at a time
+    pub(crate) fn match_market_order(
+        &mut self,
+        owner: Option<OwnerId>,
+        side: OrderSide,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<ExecutionReport, BookError> {
+        let plan = self.plan_market_order(self.next_id, owner, side, quantity, options)?;
+        // A rejected order never reaches the band, so it leaves trading alone
+        plan.require_complete(quantity, options)?;
+
+        // Only consume an id once the order is accepted
+        let market_order_id = self.next_order_id();
+        debug_assert_eq!(market_order_id, plan.taker_order_id);
+        self.apply_match(side, &plan);
+        self.interrupt_after_fills(side, &plan);
+
+        Ok(plan.into_market_report(side, quantity))
+    }
+
+    // Validate a market order and plan its fills. Whether the plan is good
+    // enough for an order that must fill completely is up to the caller.
+    pub(crate) fn plan_market_order(
+        &self,
+        taker_order_id: OrderId,
+        taker_owner: Option<OwnerId>,
+        side: OrderSide,
+        quantity: Decimal,
+        options: MarketOrderOptions,
+    ) -> Result<MatchPlan, BookError> {
+        self.require_phase(TradingPhase::accepts_market_orders)?;
+
+        if quantity <= Decimal::ZERO {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        self.spec.check_quantity(quantity)?;
+
+        // Market orders have no price, so the minimum notional is checked
+        // against the best opposite price at arrival
+        let best_opposite = match side {
+            OrderSide::Buy => self.best_ask(),
+            OrderSide::Sell => self.best_bid(),
+        };
+        if let Some(price) = best_opposite {
+            self.spec.check_notional(price * quantity)?;
+        }
+
+        let protection_price = match options.max_slippage {
+            Some(max_slippage) if max_slippage < Decimal::ZERO => {
+                return Err(BookError::InvalidSlippage);
+            }
+            Some(max_slippage) => match side {
+                OrderSide::Buy => self.best_ask().map(|ask| ask + max_slippage),
+                OrderSide::Sell => self.best_bid().map(|bid| bid - max_slippage),
+            },
+            None => None,
+        };
+
         // Quick liquidity check first
         let available = match side {
             OrderSide::Buy => self.total_ask_volume,
             OrderSide::Sell => self.total_bid_volume,
         };
 
-        if available < quantity {
-            return Err(eyre::eyre!("Insufficient liquidity for market order"));
+        if !options.allow_partial && available < quantity {
+            return Err(BookError::InsufficientLiquidity);
         }
 
-        let mut remaining_quantity = quantity;
-        let mut fills = Vec::new();
-        let market_order_id = self.next_order_id();
+        let plan = self.plan_match(
+            taker_order_id,
+            taker_owner,
+            side,
+            protection_price,
+            MatchBudget::Quantity(quantity),
+        );
+
+        // Risk is checked against what the order would actually trade, at
+        // every level it sweeps rather than just the best one
+        if let Some(owner) = taker_owner {
+            let worst_price = plan.fills.last().map(|fill| fill.price);
+            self.check_risk(owner, side, quantity, plan.notional, worst_price, false)?;
+        }
 
-        // Choose the book side we're matching against
-        let book_side = match side {
-            OrderSide::Buy => &mut self.asks,  // Lowest asks first
-            OrderSide::Sell => &mut self.bids, // Highest bids first
+        Ok(plan)
+    }
+
+    /// Simulate `add_limit_order` without modifying the book.
+    ///
+    /// Runs the same matching logic against the current book and returns
+    /// exactly what `add_limit_order` would return if called now, including
+    /// the `OrderId` the order would be assigned and the quantity that
+    /// would rest in the book.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+    ///
+    /// let simulated = book.simulate_limit_order(OrderSide::Buy, dec!(100.00), dec!(15)).unwrap();
+    /// assert_eq!(simulated.fills[0].quantity, dec!(10));
+    /// assert_eq!(simulated.leaves_quantity, dec!(5));
+    ///
+    /// let executed = book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(15)).unwrap();
+    /// assert_eq!(simulated, executed);
+    /// ```
+    pub fn simulate_limit_order(
+        &self,
+        order_side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        let tick = self.validate_limit_order(order_side, price, quantity)?;
+
+        let plan = self.plan_limit_order(self.next_id, None, order_side, &tick, quantity);
+        Ok(plan.into_limit_report(order_side, quantity))
+    }
+
+    /// Simulate `execute_market_order` without modifying the book.
+    ///
+    /// Returns exactly the report, or the error, that `execute_market_order`
+    /// would return if called now.
+    pub fn simulate_market_order(
+        &self,
+        side: OrderSide,
+        quantity: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        let options = MarketOrderOptions::default();
+        let plan = self.plan_market_order(self.next_id, None, side, quantity, options)?;
+        plan.require_complete(quantity, options)?;
+        Ok(plan.into_market_report(side, quantity))
+    }
+
+    /// Execute a market order sized in quote currency ("buy $1000 worth").
+    ///
+    /// Consumes levels in the same price-time priority as
+    /// `execute_market_order` until the notional budget is spent. Where the
+    /// remaining budget only covers part of a level, the quantity is rounded
+    /// to a multiple of `lot_size` as set by `with_lot_rounding`, down by
+    /// default so the budget is never exceeded. `lot_size` must be a
+    /// multiple of the instrument's lot size, the budget must meet its
+    /// minimum notional and the quantity it would fill must be within its
+    /// minimum and maximum order size.
+    ///
+    /// Matching stops at the first level where the remaining budget is too
+    /// small for a lot, even when a lot would cost less further down the
+    /// book, so price priority holds on both sides.
+    ///
+    /// Running out of liquidity is not an error, the order is cancelled
+    /// with whatever could not be spent reported as `unspent_notional`.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide, OrderStatus};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(5)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(10)).unwrap();
+    ///
+    /// // 500 buys the 100.00 level, the remaining 500 buys 4.95 at 101.00
+    /// let report = book
+    ///     .execute_notional_market_order(OrderSide::Buy, dec!(1000), dec!(0.01))
+    ///     .unwrap();
+    /// assert_eq!(report.status, OrderStatus::Filled);
+    /// assert_eq!(report.filled_quantity, dec!(9.95));
+    /// assert_eq!(report.filled_notional, dec!(999.95));
+    /// assert_eq!(report.unspent_notional, Some(dec!(0.05)));
+    /// ```
+    pub fn execute_notional_market_order(
+        &mut self,
+        side: OrderSide,
+        notional: Decimal,
+        lot_size: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_notional_market_order(None, side, notional, lot_size)
+    }
+
+    /// Execute a notional market order on behalf of `owner`, see
+    /// `execute_market_order_for`
+    pub fn execute_notional_market_order_for(
+        &mut self,
+        owner: OwnerId,
+        side: OrderSide,
+        notional: Decimal,
+        lot_size: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        self.submit_notional_market_order(Some(owner), side, notional, lot_size)
+    }
+
+    fn submit_notional_market_order(
+        &mut self,
+        owner: Option<OwnerId>,
+        side: OrderSide,
+        notional: Decimal,
+        lot_size: Decimal,
+    ) -> Result<ExecutionReport, BookError> {
+        self.require_phase(TradingPhase::accepts_market_orders)?;
+
+        if notional <= Decimal::ZERO {
+            return Err(BookError::InvalidNotional);
+        }
+
+        if lot_size <= Decimal::ZERO {
+            return Err(BookError::InvalidLotSize);
+        }
+
+        // Rounding to a multiple of the instrument lot keeps every fill on a lot
+        if let Some(instrument_lot) = self.spec.lot_size {
+            if !(lot_size % instrument_lot).is_zero() {
+                return Err(BookError::LotSizeViolation);
+            }
+        }
+
+        self.spec.check_notional(notional)?;
+
+        let budget = MatchBudget::Notional {
+            amount: notional,
+            lot_size,
+            rounding: self.lot_rounding,
         };
+        let plan = self.plan_match(self.next_id, owner, side, None, budget);
 
-        while remaining_quantity > Decimal::ZERO {
-            // Get best price level
-            let best_price_entry = match side {
-                OrderSide::Buy => book_side.first_entry(), // Lowest ask
-                OrderSide::Sell => book_side.last_entry(), // Highest bid
-            };
+        // The quantity is only known once planned, it must still be a
+        // valid order size for the instrument
+        if plan.filled_quantity > Decimal::ZERO {
+            self.spec.check_quantity(plan.filled_quantity)?;
+        }
 
-            let mut entry = best_price_entry
-                .ok_or_else(|| eyre::eyre!("Insufficient liquidity for market order"))?;
+        if let Some(owner) = owner {
+            let worst_price = plan.fills.last().map(|fill| fill.price);
+            let quantity = plan.filled_quantity;
+            self.check_risk(owner, side, quantity, plan.notional, worst_price, false)?;
+        }
 
-            // Get the price level first
-            let price_level = entry.key().level();
+        // Only consume an id once the order is accepted
+        let market_order_id = self.next_order_id();
+        debug_assert_eq!(market_order_id, plan.taker_order_id);
+        self.apply_match(side, &plan);
+        self.interrupt_after_fills(side, &plan);
 
-            // Then get mutable access to orders
-            let orders = entry.get_mut();
+        self.trigger_stops();
+        Ok(plan.into_notional_report(side, notional))
+    }
 
-            // Match against orders at this level in time priority
-            while remaining_quantity > Decimal::ZERO && !orders.orders.is_empty() {
-                let resting_order = orders
-                    .orders
-                    .front_mut()
-                    .ok_or_else(|| eyre::eyre!("No orders at price level"))?;
+    // Work out every fill an incoming order would produce without touching
+    // the book. Both the mutating and the simulated paths go through here,
+    // so a simulation always agrees with the real call.
+    //
+    // A `limit_price` of None matches at any price (market order). Either
+    // way matching never goes beyond the price band or the volatility
+    // breaker limit.
+    pub(crate) fn plan_match(
+        &self,
+        taker_order_id: OrderId,
+        taker_owner: Option<OwnerId>,
+        side: OrderSide,
+        limit_price: Option<Decimal>,
+        budget: MatchBudget,
+    ) -> MatchPlan {
+        let mut plan = MatchPlan::new(taker_order_id);
+        let band_limit = self.band_limit(side);
+        let volatility_limit = self.volatility_limit(side);
+
+        for (tick, orders) in self.taker_levels(side) {
+            let crosses = match (side, limit_price) {
+                (_, None) => true,
+                (OrderSide::Buy, Some(price)) => price >= tick.level(),
+                (OrderSide::Sell, Some(price)) => price <= tick.level(),
+            };
+            if !crosses {
+                plan.stop = MatchStop::PriceLimit;
+                break; // Price no longer crosses
+            }
 
-                let fill_quantity = remaining_quantity.min(resting_order.quantity);
+            // Quantity still wanted at this price
+            let level_quantity = match budget {
+                MatchBudget::Quantity(quantity) => quantity - plan.filled_quantity,
+                MatchBudget::Notional {
+                    amount,
+                    lot_size,
+                    rounding,
+                } => {
+                    let lots = (amount - plan.notional) / tick.level() / lot_size;
+                    let lots = match rounding {
+                        LotRounding::Down => lots.floor(),
+                        LotRounding::Nearest => {
+                            lots.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+                        }
+                        LotRounding::Up => lots.ceil(),
+                    };
+                    lots * lot_size
+                }
+            };
+            if level_quantity <= Decimal::ZERO {
+                plan.stop = MatchStop::BudgetUsed;
+                break; // Budget used up, or too little left for a lot here
+            }
 
-                fills.push(Fill {
-                    quantity: fill_quantity,
-                    price: price_level, // Use stored price_level instead of entry.key()
-                    taker_order_id: market_order_id,
-                    maker_order_id: resting_order.id,
-                });
-
-                // Update quantities and totals
-                remaining_quantity -= fill_quantity;
-                orders.total_volume -= fill_quantity;
-                match side {
-                    OrderSide::Buy => self.total_ask_volume -= fill_quantity,
-                    OrderSide::Sell => self.total_bid_volume -= fill_quantity,
+            if let Some(limit) = band_limit.filter(|limit| beyond(side, tick.level(), *limit)) {
+                plan.stop = MatchStop::PriceBand(limit);
+                break; // Would trade through the band
+            }
+            if let Some(limit) = volatility_limit.filter(|limit| beyond(side, tick.level(), *limit))
+            {
+                plan.stop = MatchStop::Volatility(limit);
+                break; // Would move the price too far too fast
+            }
+
+            let allocations = self.allocation.allocate(orders, level_quantity);
+            for (resting_order, fill_quantity) in orders.orders.iter().zip(&allocations) {
+                if *fill_quantity == Decimal::ZERO {
+                    continue;
                 }
 
-                // Remove filled order from lookup and book
-                if fill_quantity == resting_order.quantity {
-                    let removed_order = orders.orders.pop_front().unwrap();
-                    orders.order_count -= 1;
-                    self.order_lookup.remove(&removed_order.id);
+                let mut fill = Fill {
+                    quantity: *fill_quantity,
+                    price: tick.level(),
+                    taker_order_id,
+                    maker_order_id: resting_order.id,
+                    taker_side: side,
+                    taker_owner,
+                    maker_owner: resting_order.owner,
+                    taker_fee: Decimal::ZERO,
+                    maker_fee: Decimal::ZERO,
+                    fee_currency: FeeCurrency::default(),
+                };
+                self.charge_fees(&mut fill);
+                plan.fills.push(fill);
+                plan.filled_quantity += *fill_quantity;
+                plan.notional += *fill_quantity * tick.level();
+            }
+
+            plan.levels.push(LevelMatch {
+                tick: tick.clone(),
+                allocations,
+            });
+
+            if level_quantity < orders.total_volume {
+                plan.stop = MatchStop::BudgetUsed;
+                break; // Level only partially consumed
+            }
+            plan.stop = MatchStop::BookExhausted; // Unless a later level stops it
+        }
+
+        plan
+    }
+
+    // Apply a plan produced by `plan_match` against the current book state
+    pub(crate) fn apply_match(&mut self, side: OrderSide, plan: &MatchPlan) {
+        self.remove_matched(side, plan);
+        self.record_fills(&plan.fills);
+    }
+
+    // Take the planned quantities out of the resting orders
+    pub(crate) fn remove_matched(&mut self, side: OrderSide, plan: &MatchPlan) {
+        let book_side = match side {
+            OrderSide::Buy => &mut self.asks,
+            OrderSide::Sell => &mut self.bids,
+        };
+
+        for level in &plan.levels {
+            let orders = book_side
+                .get_mut(&level.tick)
+                .expect("planned level missing from book");
+
+            let mut matched = Decimal::ZERO;
+            for (resting_order, fill_quantity) in orders.orders.iter_mut().zip(&level.allocations) {
+                resting_order.quantity -= *fill_quantity;
+                matched += *fill_quantity;
+                if *fill_quantity > Decimal::ZERO {
+                    risk::release_resting(&mut self.accounts, resting_order, *fill_quantity);
                 }
             }
+            orders.total_volume -= matched;
+            match side {
+                OrderSide::Buy => self.total_ask_volume -= matched,
+                OrderSide::Sell => self.total_bid_volume -= matched,
+            }
+
+            // Remove filled orders from lookup and book
+            let order_lookup = &mut self.order_lookup;
+            orders.orders.retain(|order| {
+                let filled = order.quantity == Decimal::ZERO;
+                if filled {
+                    order_lookup.remove(&order.id);
+                }
+                !filled
+            });
+            orders.order_count = orders.orders.len();
 
             // Remove empty price levels
             if orders.order_count == 0 {
-                entry.remove();
+                book_side.remove(&level.tick);
             }
         }
+    }
+
+    // Book trades that happened against everything that follows them
+    pub(crate) fn record_fills(&mut self, fills: &[Fill]) {
+        if let Some(fill) = fills.last() {
+            self.reference_price = Some(fill.price);
+        }
+        self.record_trades(fills);
+        self.record_volumes(fills);
+        self.positions.apply_fills(fills);
+    }
+
+    // Pause or call an auction when matching stopped at a band or the
+    // volatility breaker
+    fn interrupt_trading(&mut self, side: OrderSide, stop: MatchStop) {
+        match stop {
+            MatchStop::PriceBand(limit) => self.pause_at_band(side, limit),
+            MatchStop::Volatility(limit) => self.start_volatility_auction(side, limit),
+            _ => {}
+        }
+    }
 
-        Ok(fills)
+    // Market orders never rest, so one that stops at a band or the breaker
+    // without trading leaves nothing in the book to justify a pause
+    fn interrupt_after_fills(&mut self, side: OrderSide, plan: &MatchPlan) {
+        if !plan.fills.is_empty() {
+            self.interrupt_trading(side, plan.stop);
+        }
+    }
+
+    // Levels an incoming order on `side` matches against, in price priority.
+    // Shared by the read-only walks so they follow the matching order exactly.
+    pub(crate) fn taker_levels(
+        &self,
+        side: OrderSide,
+    ) -> Box<dyn Iterator<Item = (&Tick, &Orders)> + '_> {
+        match side {
+            OrderSide::Buy => Box::new(self.asks.iter()), // Lowest asks first
+            OrderSide::Sell => Box::new(self.bids.iter().rev()), // Highest bids first
+        }
     }
 
     /// Helpers
@@ -524,6 +1409,15 @@ impl OrderBook {
         }
     }
 
+    /// Get the mid price ((best_bid + best_ask) / 2)
+    /// Returns None if either side is empty
+    pub fn mid_price(&self) -> Option<Decimal> {
+        match (self.best_ask(), self.best_bid()) {
+            (Some(ask), Some(bid)) => Some((ask + bid) / Decimal::TWO),
+            _ => None,
+        }
+    }
+
     /// Get the best bid and ask prices
     /// Returns (bid, ask) tuple, either value may be None
     pub fn best_prices(&self) -> (Option<Decimal>, Option<Decimal>) {
@@ -543,12 +1437,32 @@ impl OrderBook {
             .first_key_value()
             .map(|(_, orders)| orders.total_volume)
     }
+
+    /// Get up to `depth` aggregated bid levels as `(price, volume)`, best first
+    pub fn bid_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.bids
+            .iter()
+            .rev()
+            .take(depth)
+            .map(|(tick, orders)| (tick.level(), orders.total_volume))
+            .collect()
+    }
+
+    /// Get up to `depth` aggregated ask levels as `(price, volume)`, best first
+    pub fn ask_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.asks
+            .iter()
+            .take(depth)
+            .map(|(tick, orders)| (tick.level(), orders.total_volume))
+            .collect()
+    }
 }
 
 // tests
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::allocation::ProRata;
     use rust_decimal_macros::dec;
 
     #[test]
@@ -556,13 +1470,14 @@ mod tests {
         let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive"); // 0.01 tick size
 
         // Add a buy order
-        let (order_id, fills) = book
+        let report = book
             .add_limit_order(
                 OrderSide::Buy,
                 dec!(100.00), // Price
                 dec!(10),     // 10 units
             )
             .expect("invalid order");
+        let (order_id, fills) = (report.order_id, report.fills);
 
         assert!(fills.is_empty()); // No fills yet as book was empty
         assert_eq!(book.bids.len(), 1); // One price level
@@ -586,9 +1501,10 @@ mod tests {
         assert_eq!(book.total_ask_volume, dec!(0));
 
         // Add a sell order and check both sides
-        let (_sell_id, _) = book
+        let _sell_id = book
             .add_limit_order(OrderSide::Sell, dec!(101.00), dec!(20))
-            .expect("invalid order");
+            .expect("invalid order")
+            .order_id;
 
         assert_eq!(book.total_bid_volume, dec!(10));
         assert_eq!(book.total_ask_volume, dec!(20));
@@ -599,27 +1515,33 @@ mod tests {
         let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
 
         // Create initial book state with some asks
-        let (sell_id1, _) = book
+        let sell_id1 = book
             .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(50))
-            .expect("invalid order"); // Best price
-        let (sell_id2, _) = book
+            .expect("invalid order")
+            .order_id; // Best price
+        let sell_id2 = book
             .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(25))
-            .expect("invalid order"); // Same price, later time
-        let (sell_id3, _) = book
+            .expect("invalid order")
+            .order_id; // Same price, later time
+        let sell_id3 = book
             .add_limit_order(OrderSide::Sell, dec!(101.00), dec!(75))
-            .expect("invalid order"); // Worse price
+            .expect("invalid order")
+            .order_id; // Worse price
 
         // Verify initial state
         assert_eq!(book.total_ask_volume, dec!(150));
 
         // Add a limit buy that crosses the book
-        let (buy_id, fills) = book
+        let report = book
             .add_limit_order(
                 OrderSide::Buy,
                 dec!(101.00), // Willing to pay up to 101.00
                 dec!(100),    // Want 100 units
             )
             .expect("invalid order");
+        assert_eq!(report.status, OrderStatus::Filled);
+        assert_eq!(report.leaves_quantity, dec!(0));
+        let (buy_id, fills) = (report.order_id, report.fills);
 
         // Should get same fills as market order test
         assert_eq!(fills.len(), 3);
@@ -649,13 +1571,15 @@ mod tests {
         assert!(book.order_lookup.contains_key(&sell_id3)); // Partially filled order should remain
 
         // Add a limit buy that doesn't cross
-        let (buy_id2, fills2) = book
+        let report = book
             .add_limit_order(
                 OrderSide::Buy,
                 dec!(99.00), // Below best ask
                 dec!(25),
             )
             .expect("invalid order");
+        assert_eq!(report.status, OrderStatus::New);
+        let (buy_id2, fills2) = (report.order_id, report.fills);
 
         // Should get no fills
         assert!(fills2.is_empty());
@@ -668,15 +1592,18 @@ mod tests {
         let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
 
         // Add a few orders to create a known state
-        let (buy_id1, _) = book
+        let buy_id1 = book
             .add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
-            .expect("invalid order");
-        let (buy_id2, _) = book
+            .expect("invalid order")
+            .order_id;
+        let buy_id2 = book
             .add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20))
-            .expect("invalid order"); // same tick level
-        let (_sell_id1, _) = book
+            .expect("invalid order")
+            .order_id; // same tick level
+        let _sell_id1 = book
             .add_limit_order(OrderSide::Sell, dec!(101.00), dec!(15))
-            .expect("invalid order");
+            .expect("invalid order")
+            .order_id;
 
         // Initial state verification
         assert_eq!(book.bids.len(), 1); // one tick level
@@ -709,10 +1636,10 @@ mod tests {
 
         // Error cases
         let result = book.cancel_limit_order(999); // non-existent order
-        assert!(result.is_err());
+        assert_eq!(result, Err(BookError::OrderNotFound(999)));
 
         let result = book.cancel_limit_order(buy_id1); // already cancelled order
-        assert!(result.is_err());
+        assert_eq!(result, Err(BookError::OrderNotFound(buy_id1)));
 
         // Verify totals unchanged after failed cancels
         assert_eq!(book.total_bid_volume, dec!(0));
@@ -724,23 +1651,28 @@ mod tests {
         let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
 
         // Create ask book with multiple price levels and times
-        let (sell_id1, _) = book
+        let sell_id1 = book
             .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(50))
-            .expect("invalid order"); // Best price
-        let (sell_id2, _) = book
+            .expect("invalid order")
+            .order_id; // Best price
+        let sell_id2 = book
             .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(25))
-            .expect("invalid order"); // Same price, later time
-        let (sell_id3, _) = book
+            .expect("invalid order")
+            .order_id; // Same price, later time
+        let sell_id3 = book
             .add_limit_order(OrderSide::Sell, dec!(101.00), dec!(75))
-            .expect("invalid order"); // Worse price
+            .expect("invalid order")
+            .order_id; // Worse price
 
         // Verify initial state
         assert_eq!(book.total_ask_volume, dec!(150));
 
         // Execute market buy for 100 units
-        let fills = book
+        let report = book
             .execute_market_order(OrderSide::Buy, dec!(100))
             .expect("Market order should execute");
+        assert_eq!(report.status, OrderStatus::Filled);
+        let fills = report.fills;
 
         // Should get 3 fills
         assert_eq!(fills.len(), 3);
@@ -800,4 +1732,590 @@ mod tests {
         assert_eq!(bid, Some(dec!(100.00)));
         assert_eq!(ask, Some(dec!(101.00)));
     }
+
+    #[test]
+    fn test_partial_fill_reduces_resting_order() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        let sell_id = book
+            .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("invalid order")
+            .order_id;
+
+        book.execute_market_order(OrderSide::Buy, dec!(4))
+            .expect("Market order should execute");
+        let tick = Tick::new(dec!(100.00), dec!(0.01)).expect("invalid tick");
+        assert_eq!(book.asks.get(&tick).unwrap().orders[0].quantity, dec!(6));
+
+        // The next taker only gets what is left of the resting order
+        let report = book
+            .add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        let fills = report.fills;
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].quantity, dec!(6));
+        assert_eq!(fills[0].maker_order_id, sell_id);
+        assert_eq!(book.total_ask_volume, dec!(0));
+        assert_eq!(book.total_bid_volume, dec!(4));
+    }
+
+    #[test]
+    fn test_simulation_matches_execution() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(7))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.50), dec!(3))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(20))
+            .expect("invalid order");
+
+        // Limit order that sweeps two levels and partially rests
+        let simulated = book
+            .simulate_limit_order(OrderSide::Buy, dec!(100.50), dec!(20))
+            .expect("invalid order");
+        assert_eq!(book.total_ask_volume, dec!(35)); // Untouched
+        let executed = book
+            .add_limit_order(OrderSide::Buy, dec!(100.50), dec!(20))
+            .expect("invalid order");
+        assert_eq!(simulated, executed);
+        assert_eq!(book.best_bid_volume(), Some(dec!(5)));
+
+        // Market orders, including the insufficient liquidity error
+        let simulated = book
+            .simulate_market_order(OrderSide::Buy, dec!(8))
+            .expect("Market order should execute");
+        let executed = book
+            .execute_market_order(OrderSide::Buy, dec!(8))
+            .expect("Market order should execute");
+        assert_eq!(simulated, executed);
+        assert!(book
+            .simulate_market_order(OrderSide::Buy, dec!(13))
+            .is_err());
+        assert!(book.execute_market_order(OrderSide::Buy, dec!(13)).is_err());
+    }
+
+    #[test]
+    fn test_notional_market_order() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        let bid_id1 = book
+            .add_limit_order(OrderSide::Buy, dec!(50.00), dec!(2))
+            .expect("invalid order")
+            .order_id;
+        let bid_id2 = book
+            .add_limit_order(OrderSide::Buy, dec!(40.00), dec!(10))
+            .expect("invalid order")
+            .order_id;
+
+        // 100 sells the 50.00 level, 150 buys 3.75 at 40.00 -> 3.7 with 0.1 lots
+        let report = book
+            .execute_notional_market_order(OrderSide::Sell, dec!(250), dec!(0.1))
+            .expect("valid order");
+        assert_eq!(report.status, OrderStatus::Filled);
+        assert_eq!(report.fills.len(), 2);
+        assert_eq!(report.fills[0].maker_order_id, bid_id1);
+        assert_eq!(report.fills[1].maker_order_id, bid_id2);
+        assert_eq!(report.fills[1].quantity, dec!(3.7));
+        assert_eq!(report.filled_quantity, dec!(5.7));
+        assert_eq!(report.filled_notional, dec!(248.00));
+        assert_eq!(report.unspent_notional, Some(dec!(2.00)));
+        assert_eq!(book.total_bid_volume, dec!(6.3));
+
+        // Budget below one lot at the best price fills nothing
+        let report = book
+            .execute_notional_market_order(OrderSide::Sell, dec!(3), dec!(0.1))
+            .expect("valid order");
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert!(report.fills.is_empty());
+        assert_eq!(report.unspent_notional, Some(dec!(3)));
+
+        // Budget larger than the book spends what is available
+        let report = book
+            .execute_notional_market_order(OrderSide::Sell, dec!(1000), dec!(0.1))
+            .expect("valid order");
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.filled_quantity, dec!(6.3));
+        assert_eq!(report.unspent_notional, Some(dec!(748.00)));
+        assert_eq!(report.reject_reason, Some(BookError::InsufficientLiquidity));
+        assert!(book.bids.is_empty());
+
+        assert!(book
+            .execute_notional_market_order(OrderSide::Sell, dec!(0), dec!(0.1))
+            .is_err());
+        assert!(book
+            .execute_notional_market_order(OrderSide::Sell, dec!(10), dec!(0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_notional_sell_stops_at_unaffordable_bid() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Buy, dec!(50.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(30.00), dec!(5))
+            .expect("invalid order");
+
+        // 80 sells one lot at 50.00. The remaining 30 would be a lot at
+        // 30.00, but the 50.00 bid still holds volume and goes first.
+        let report = book
+            .execute_notional_market_order(OrderSide::Sell, dec!(80), dec!(1))
+            .expect("valid order");
+        assert_eq!(report.status, OrderStatus::Filled);
+        assert_eq!(report.filled_quantity, dec!(1));
+        assert_eq!(report.fills.len(), 1);
+        assert_eq!(report.fills[0].price, dec!(50.00));
+        assert_eq!(report.unspent_notional, Some(dec!(30)));
+        assert_eq!(
+            book.bid_levels(5),
+            vec![(dec!(50.00), dec!(4)), (dec!(30.00), dec!(5))]
+        );
+
+        // Less than a lot at the best bid
+        let report = book
+            .execute_notional_market_order(OrderSide::Sell, dec!(40), dec!(1))
+            .expect("valid order");
+        assert_eq!(report.reject_reason, Some(BookError::NotionalBelowLot));
+        assert_eq!(book.total_bid_volume, dec!(9));
+    }
+
+    #[test]
+    fn test_notional_lot_rounding() {
+        let buy = |rounding, notional| {
+            let mut book = OrderBook::new(dec!(0.01))
+                .expect("tick spacing must be positive")
+                .with_lot_rounding(rounding);
+            book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+                .expect("invalid order");
+            let report = book
+                .execute_notional_market_order(OrderSide::Buy, notional, dec!(1))
+                .expect("valid order");
+            (report.filled_quantity, report.unspent_notional)
+        };
+
+        assert_eq!(buy(LotRounding::Down, dec!(250)), (dec!(2), Some(dec!(50))));
+        assert_eq!(
+            buy(LotRounding::Nearest, dec!(250)),
+            (dec!(3), Some(dec!(-50)))
+        );
+        assert_eq!(
+            buy(LotRounding::Nearest, dec!(230)),
+            (dec!(2), Some(dec!(30)))
+        );
+        assert_eq!(buy(LotRounding::Up, dec!(230)), (dec!(3), Some(dec!(-70))));
+        assert_eq!(buy(LotRounding::Up, dec!(200)), (dec!(2), Some(dec!(0))));
+    }
+
+    #[test]
+    fn test_market_order_partial_fill_and_protection() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(90.00), dec!(10))
+            .expect("invalid order");
+
+        // All-or-error within the protection price rejects without touching the book
+        let protected = MarketOrderOptions {
+            allow_partial: false,
+            max_slippage: Some(dec!(1.00)),
+        };
+        let result = book.execute_market_order_with(OrderSide::Sell, dec!(25), protected);
+        assert!(result.is_err());
+        assert_eq!(book.total_bid_volume, dec!(30));
+
+        // Same order allowed to fill partially stops at 99.00
+        let report = book
+            .execute_market_order_with(
+                OrderSide::Sell,
+                dec!(25),
+                MarketOrderOptions {
+                    allow_partial: true,
+                    ..protected
+                },
+            )
+            .expect("valid order");
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.fills.len(), 2);
+        assert_eq!(report.filled_quantity, dec!(20));
+        assert_eq!(report.cancelled_quantity, dec!(5));
+        assert_eq!(
+            report.reject_reason,
+            Some(BookError::ProtectionPriceReached)
+        );
+        assert_eq!(book.best_bid(), Some(dec!(90.00)));
+
+        // Partial fill without protection takes everything and reports the rest
+        let report = book
+            .execute_market_order_with(
+                OrderSide::Sell,
+                dec!(25),
+                MarketOrderOptions {
+                    allow_partial: true,
+                    max_slippage: None,
+                },
+            )
+            .expect("valid order");
+        assert_eq!(report.filled_quantity, dec!(10));
+        assert_eq!(report.cancelled_quantity, dec!(15));
+        assert_eq!(report.reject_reason, Some(BookError::InsufficientLiquidity));
+        assert!(book.bids.is_empty());
+    }
+
+    #[test]
+    fn test_tick_policy_matches_and_rests_at_same_price() {
+        // Nearest: 100.017 becomes 100.02 and crosses the 100.02 ask
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Sell, dec!(100.02), dec!(5))
+            .expect("invalid order");
+        let report = book
+            .add_limit_order(OrderSide::Buy, dec!(100.017), dec!(8))
+            .expect("invalid order");
+        assert_eq!(report.filled_quantity, dec!(5));
+        assert_eq!(book.best_bid(), Some(dec!(100.02)));
+        assert_eq!(book.best_ask(), None);
+
+        // Conservative: the buy rounds down to 100.01 and rests without crossing
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_tick_policy(TickPolicy::Conservative);
+        book.add_limit_order(OrderSide::Sell, dec!(100.02), dec!(5))
+            .expect("invalid order");
+        let report = book
+            .add_limit_order(OrderSide::Buy, dec!(100.017), dec!(8))
+            .expect("invalid order");
+        assert!(report.fills.is_empty());
+        assert_eq!(book.best_bid(), Some(dec!(100.01)));
+        assert_eq!(book.best_ask(), Some(dec!(100.02)));
+
+        // Reject: nothing happens and no id is consumed
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_tick_policy(TickPolicy::Reject);
+        let result = book.add_limit_order(OrderSide::Buy, dec!(100.017), dec!(8));
+        assert_eq!(result, Err(BookError::PriceNotAligned));
+        assert_eq!(book.next_id, 0);
+        assert!(book.bids.is_empty());
+    }
+
+    #[test]
+    fn test_instrument_spec_enforced_on_every_path() {
+        let spec = InstrumentSpec::new(dec!(0.01))
+            .with_lot_size(dec!(0.1))
+            .with_min_quantity(dec!(1))
+            .with_max_quantity(dec!(50))
+            .with_min_notional(dec!(150));
+        let mut book = OrderBook::from_spec(spec).expect("valid spec");
+
+        // Limit orders
+        assert_eq!(
+            book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(2.05)),
+            Err(BookError::LotSizeViolation)
+        );
+        assert_eq!(
+            book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(0.5)),
+            Err(BookError::BelowMinQuantity)
+        );
+        assert_eq!(
+            book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(60)),
+            Err(BookError::AboveMaxQuantity)
+        );
+        assert_eq!(
+            book.simulate_limit_order(OrderSide::Sell, dec!(100.00), dec!(1)),
+            Err(BookError::BelowMinNotional)
+        );
+        let resting = book
+            .add_limit_order(OrderSide::Sell, dec!(100.00), dec!(20))
+            .expect("valid order");
+        assert_eq!(resting.order_id, 0); // Rejects consume no ids
+
+        // Market orders, min notional checked at the best ask
+        assert_eq!(
+            book.execute_market_order(OrderSide::Buy, dec!(1.25)),
+            Err(BookError::LotSizeViolation)
+        );
+        assert_eq!(
+            book.simulate_market_order(OrderSide::Buy, dec!(1)),
+            Err(BookError::BelowMinNotional)
+        );
+        let report = book
+            .execute_market_order(OrderSide::Buy, dec!(2))
+            .expect("valid order");
+        assert_eq!(report.order_id, 1);
+
+        // Notional orders must round to instrument lots and meet the minimum
+        assert_eq!(
+            book.execute_notional_market_order(OrderSide::Buy, dec!(500), dec!(0.05)),
+            Err(BookError::LotSizeViolation)
+        );
+        assert_eq!(
+            book.execute_notional_market_order(OrderSide::Buy, dec!(100), dec!(0.1)),
+            Err(BookError::BelowMinNotional)
+        );
+        let report = book
+            .execute_notional_market_order(OrderSide::Buy, dec!(505), dec!(0.1))
+            .expect("valid order");
+        assert_eq!(report.filled_quantity, dec!(5));
+
+        // Notional orders must also plan a valid order size
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(50))
+            .expect("valid order");
+        assert_eq!(
+            book.execute_notional_market_order(OrderSide::Buy, dec!(5200), dec!(0.1)),
+            Err(BookError::AboveMaxQuantity)
+        );
+        assert_eq!(book.total_ask_volume, dec!(63));
+        let mut book =
+            OrderBook::from_spec(InstrumentSpec::new(dec!(0.01)).with_min_quantity(dec!(5)))
+                .expect("valid spec");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10))
+            .expect("valid order");
+        assert_eq!(
+            book.execute_notional_market_order(OrderSide::Buy, dec!(300), dec!(1)),
+            Err(BookError::BelowMinQuantity)
+        );
+        let report = book
+            .execute_notional_market_order(OrderSide::Buy, dec!(500), dec!(1))
+            .expect("valid order");
+        assert_eq!(report.order_id, 1);
+
+        assert_eq!(
+            OrderBook::from_spec(InstrumentSpec::new(dec!(0.01)).with_min_notional(dec!(-1))).err(),
+            Some(BookError::InvalidNotional)
+        );
+    }
+
+    #[test]
+    fn test_tick_table_across_band_boundary() {
+        let table = TickTable::new(vec![(dec!(0), dec!(0.0001)), (dec!(1), dec!(0.01))])
+            .expect("valid table");
+        let spec = InstrumentSpec::new(dec!(0.01)).with_tick_table(table);
+        let mut book = OrderBook::from_spec(spec)
+            .expect("valid spec")
+            .with_tick_policy(TickPolicy::Reject);
+
+        book.add_limit_order(OrderSide::Buy, dec!(0.9999), dec!(10))
+            .expect("aligned below 1.00");
+        book.add_limit_order(OrderSide::Sell, dec!(1.02), dec!(10))
+            .expect("aligned above 1.00");
+        assert_eq!(
+            book.add_limit_order(OrderSide::Sell, dec!(1.0150), dec!(10)),
+            Err(BookError::PriceNotAligned)
+        );
+
+        // Improving the bid by one tick crosses into the 0.01 band
+        let tick_table = &book.spec().tick_table;
+        let best_bid = book.best_bid().expect("bid");
+        let best_ask = book.best_ask().expect("ask");
+        assert_eq!(tick_table.tick_up(best_bid), dec!(1.00));
+        assert_eq!(tick_table.tick_down(best_ask), Some(dec!(1.01)));
+        assert_eq!(tick_table.tick_down(dec!(1.00)), Some(best_bid));
+
+        let improved = tick_table.tick_up(best_bid);
+        book.add_limit_order(OrderSide::Buy, improved, dec!(5))
+            .expect("valid order");
+        assert_eq!(book.best_bid(), Some(dec!(1.00)));
+        assert_eq!(book.spread(), Some(dec!(0.02)));
+    }
+
+    #[test]
+    fn test_band_boundary_price_is_one_level() {
+        let table = TickTable::new(vec![(dec!(0), dec!(0.0001)), (dec!(1), dec!(0.01))])
+            .expect("valid table");
+        let spec = InstrumentSpec::new(dec!(0.01)).with_tick_table(table);
+        let mut book = OrderBook::from_spec(spec)
+            .expect("valid spec")
+            .with_tick_policy(TickPolicy::Conservative);
+
+        // Rounded up from the band below, then placed from the band above
+        let first = book
+            .add_limit_order(OrderSide::Sell, dec!(0.99995), dec!(4))
+            .expect("valid order");
+        let second = book
+            .add_limit_order(OrderSide::Sell, dec!(1.00), dec!(6))
+            .expect("valid order");
+        assert_eq!(book.ask_levels(5), vec![(dec!(1.0000), dec!(10))]);
+        assert_eq!(book.asks.len(), 1);
+
+        // One queue, in time priority
+        let report = book
+            .execute_market_order(OrderSide::Buy, dec!(5))
+            .expect("enough liquidity");
+        assert_eq!(report.fills[0].maker_order_id, first.order_id);
+        assert_eq!(report.fills[1].maker_order_id, second.order_id);
+        assert_eq!(book.best_ask_volume(), Some(dec!(5)));
+    }
+
+    #[test]
+    fn test_change_tick_size() {
+        let setup = || {
+            let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+            book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+                .expect("invalid order"); // 0
+            book.add_limit_order(OrderSide::Buy, dec!(100.02), dec!(5))
+                .expect("invalid order"); // 1
+            book.add_limit_order(OrderSide::Sell, dec!(100.05), dec!(3))
+                .expect("invalid order"); // 2
+            book.add_limit_order(OrderSide::Sell, dec!(100.08), dec!(4))
+                .expect("invalid order"); // 3
+            book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(2))
+                .expect("invalid order"); // 4
+            book
+        };
+        let new_table = || TickTable::fixed(dec!(0.05));
+
+        // Reject leaves the book untouched
+        let mut book = setup();
+        assert_eq!(
+            book.change_tick_size(new_table(), TickMigration::Reject),
+            Err(BookError::PriceNotAligned)
+        );
+        assert_eq!(book.best_bid(), Some(dec!(100.02)));
+        assert_eq!(book.spec().tick_table, TickTable::fixed(dec!(0.01)));
+
+        // Cancel drops misaligned orders and their volume
+        let mut book = setup();
+        let cancelled = book
+            .change_tick_size(new_table(), TickMigration::Cancel)
+            .expect("valid change");
+        assert_eq!(cancelled, vec![1, 3]);
+        assert_eq!(book.total_bid_volume, dec!(12));
+        assert_eq!(book.total_ask_volume, dec!(3));
+        assert_eq!(book.cancel_limit_order(1), Err(BookError::OrderNotFound(1)));
+
+        // Round moves buys down and sells up, merging by arrival order
+        let mut book = setup();
+        let cancelled = book
+            .change_tick_size(new_table(), TickMigration::Round)
+            .expect("valid change");
+        assert!(cancelled.is_empty());
+        assert_eq!(book.bid_levels(5), vec![(dec!(100.00), dec!(17))]);
+        assert_eq!(
+            book.ask_levels(5),
+            vec![(dec!(100.05), dec!(3)), (dec!(100.10), dec!(4))]
+        );
+        let queue: Vec<OrderId> = book
+            .bids
+            .values()
+            .next()
+            .expect("level")
+            .orders
+            .iter()
+            .map(|order| order.id)
+            .collect();
+        assert_eq!(queue, vec![0, 1, 4]);
+
+        // Migrated orders can still be cancelled and traded
+        book.cancel_limit_order(1).expect("order 1 rests at 100.00");
+        assert_eq!(book.best_bid_volume(), Some(dec!(12)));
+        assert!(book
+            .add_limit_order(OrderSide::Buy, dec!(100.02), dec!(1))
+            .is_ok()); // Nearest policy rounds onto the new grid
+        assert_eq!(book.best_bid(), Some(dec!(100.00)));
+    }
+
+    #[test]
+    fn test_change_tick_size_onto_band_boundary() {
+        let spec = InstrumentSpec::new(dec!(0.0001));
+        let mut book = OrderBook::from_spec(spec).expect("valid spec");
+        book.add_limit_order(OrderSide::Sell, dec!(1.0000), dec!(3))
+            .expect("invalid order"); // 0
+        book.add_limit_order(OrderSide::Sell, dec!(0.9999), dec!(2))
+            .expect("invalid order"); // 1
+
+        // 0.9999 is off the new 0.0002 grid and rounds up onto the 1.00
+        // boundary, joining the level already there behind order 0
+        let table = TickTable::new(vec![(dec!(0), dec!(0.0002)), (dec!(1), dec!(0.01))])
+            .expect("valid table");
+        book.change_tick_size(table, TickMigration::Round)
+            .expect("valid migration");
+        assert_eq!(book.ask_levels(5), vec![(dec!(1.0000), dec!(5))]);
+        let (tick, orders) = book.asks.iter().next().expect("level");
+        assert_eq!(tick.tick_size(), dec!(0.01));
+        let queue: Vec<OrderId> = orders.orders.iter().map(|order| order.id).collect();
+        assert_eq!(queue, vec![0, 1]);
+
+        book.cancel_limit_order(1).expect("order 1 rests at 1.00");
+        assert_eq!(book.ask_levels(5), vec![(dec!(1.0000), dec!(3))]);
+    }
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn test_book_is_send() {
+        // A book, clock included, can be moved to a matching thread
+        assert_send::<OrderBook>();
+    }
+
+    #[test]
+    fn test_pro_rata_book() {
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_allocation(ProRata::new(dec!(1)).expect("valid lot size"));
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20))
+            .expect("invalid order"); // 0
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(60))
+            .expect("invalid order"); // 1
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20))
+            .expect("invalid order"); // 2
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10))
+            .expect("invalid order"); // 3
+
+        let simulated = book
+            .simulate_market_order(OrderSide::Sell, dec!(50))
+            .expect("valid order");
+        let report = book
+            .execute_market_order(OrderSide::Sell, dec!(50))
+            .expect("valid order");
+        assert_eq!(simulated, report);
+
+        let allocations: Vec<(OrderId, Decimal)> = report
+            .fills
+            .iter()
+            .map(|fill| (fill.maker_order_id, fill.quantity))
+            .collect();
+        assert_eq!(
+            allocations,
+            vec![(0, dec!(10)), (1, dec!(30)), (2, dec!(10))]
+        );
+        assert_eq!(book.best_bid_volume(), Some(dec!(50)));
+
+        // A sweep through the level fills it completely before moving on
+        let report = book
+            .execute_market_order(OrderSide::Sell, dec!(55))
+            .expect("valid order");
+        assert_eq!(report.fills.len(), 4);
+        assert_eq!(book.best_bid(), Some(dec!(99.00)));
+        assert_eq!(book.best_bid_volume(), Some(dec!(5)));
+    }
+
+    #[test]
+    fn test_top_order_tracking() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        let first = book
+            .add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+            .expect("invalid order")
+            .order_id;
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(10))
+            .expect("invalid order");
+        // Resting behind the best price never makes a top order
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10))
+            .expect("invalid order");
+        let improver = book
+            .add_limit_order(OrderSide::Buy, dec!(100.50), dec!(10))
+            .expect("invalid order")
+            .order_id;
+
+        let top = |book: &OrderBook, price| {
+            let tick = Tick::new(price, dec!(0.01)).expect("invalid tick");
+            book.bids.get(&tick).and_then(|orders| orders.top_order())
+        };
+        assert_eq!(top(&book, dec!(100.00)), Some(first));
+        assert_eq!(top(&book, dec!(99.00)), None);
+        assert_eq!(top(&book, dec!(100.50)), Some(improver));
+
+        // Top order status ends when the order leaves the book
+        book.cancel_limit_order(first).expect("order exists");
+        assert_eq!(top(&book, dec!(100.00)), None);
+    }
 }
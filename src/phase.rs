@@ -0,0 +1,248 @@
+use crate::error::BookError;
+use crate::event::BookEvent;
+use crate::order_book::OrderBook;
+
+/// The trading session state of a book, controlling which operations it
+/// accepts.
+///
+/// | Phase        | Limit orders        | Market orders | Cancels |
+/// |--------------|---------------------|---------------|---------|
+/// | `PreOpen`    | rest without match  | rejected      | yes     |
+/// | `Continuous` | match on arrival    | yes           | yes     |
+/// | `Auction`    | rest without match  | rejected      | yes     |
+/// | `Halted`     | rejected            | rejected      | yes     |
+/// | `Closed`     | rejected            | rejected      | yes     |
+///
+/// Allowed transitions:
+/// - `PreOpen` to `Auction` (the opening call) or `Closed`
+/// - `Continuous` to `Auction` (e.g. a closing call), `Halted` or `Closed`
+/// - `Auction` to `Continuous` only by uncrossing, or to `Halted`
+/// - `Halted` to `Auction`, so trading always resumes through a call, or `Closed`
+/// - `Closed` to `PreOpen` for the next session
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TradingPhase {
+    /// Order entry before the session, orders accumulate without matching
+    PreOpen,
+    /// Incoming orders match immediately against the book
+    Continuous,
+    /// Orders accumulate without matching until the book is uncrossed
+    Auction,
+    /// Trading is suspended, only cancels are accepted
+    Halted,
+    /// The session is over
+    Closed,
+}
+
+impl TradingPhase {
+    /// Whether limit orders are accepted
+    pub fn accepts_limit_orders(self) -> bool {
+        matches!(
+            self,
+            TradingPhase::PreOpen | TradingPhase::Continuous | TradingPhase::Auction
+        )
+    }
+
+    /// Whether market orders are accepted
+    pub fn accepts_market_orders(self) -> bool {
+        self == TradingPhase::Continuous
+    }
+
+    /// Whether incoming orders match on arrival
+    pub fn matches_on_arrival(self) -> bool {
+        self == TradingPhase::Continuous
+    }
+
+    /// Whether orders build up a crossed book for a later uncross
+    pub fn accumulates_orders(self) -> bool {
+        matches!(self, TradingPhase::PreOpen | TradingPhase::Auction)
+    }
+
+    /// Whether `transition_to(to)` is allowed from this phase. Leaving an
+    /// auction for continuous trading goes through `uncross` instead.
+    pub fn can_transition_to(self, to: TradingPhase) -> bool {
+        use TradingPhase::*;
+        matches!(
+            (self, to),
+            (PreOpen, Auction | Closed)
+                | (Continuous, Auction | Halted | Closed)
+                | (Auction, Halted)
+                | (Halted, Auction | Closed)
+                | (Closed, PreOpen)
+        )
+    }
+}
+
+impl OrderBook {
+    /// The book's current trading phase
+    pub fn phase(&self) -> TradingPhase {
+        self.phase
+    }
+
+    /// Move the book to another trading phase.
+    ///
+    /// Emits a [`BookEvent::PhaseChanged`].
+    ///
+    /// # Errors
+    /// `BookError::InvalidTransition` if the transition is not allowed, see
+    /// [`TradingPhase`].
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{BookError, BookEvent, OrderBook, OrderSide, TradingPhase};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+    ///
+    /// book.transition_to(TradingPhase::Halted).unwrap();
+    /// assert_eq!(
+    ///     book.execute_market_order(OrderSide::Buy, dec!(1)),
+    ///     Err(BookError::NotAllowedInPhase(TradingPhase::Halted))
+    /// );
+    /// assert_eq!(
+    ///     book.transition_to(TradingPhase::Continuous),
+    ///     Err(BookError::InvalidTransition {
+    ///         from: TradingPhase::Halted,
+    ///         to: TradingPhase::Continuous,
+    ///     })
+    /// );
+    ///
+    /// assert_eq!(
+    ///     book.drain_events(),
+    ///     vec![BookEvent::PhaseChanged {
+    ///         from: TradingPhase::Continuous,
+    ///         to: TradingPhase::Halted,
+    ///     }]
+    /// );
+    /// ```
+    pub fn transition_to(&mut self, to: TradingPhase) -> Result<(), BookError> {
+        if !self.phase.can_transition_to(to) {
+            return Err(BookError::InvalidTransition {
+                from: self.phase,
+                to,
+            });
+        }
+
+        self.set_phase(to);
+        Ok(())
+    }
+
+    // Change phase unconditionally and publish the change
+    pub(crate) fn set_phase(&mut self, to: TradingPhase) {
+        let from = self.phase;
+        self.phase = to;
+        if to != TradingPhase::Auction {
+            self.batch = None; // Batch mode only lasts while auctions run
+            self.volatility_auction_ends_at = None;
+        }
+        self.events.push(BookEvent::PhaseChanged { from, to });
+        self.refresh_indicative();
+    }
+
+    // Reject an operation the current phase does not accept
+    pub(crate) fn require_phase(&self, accepts: fn(TradingPhase) -> bool) -> Result<(), BookError> {
+        if accepts(self.phase) {
+            Ok(())
+        } else {
+            Err(BookError::NotAllowedInPhase(self.phase))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::OrderSide;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_session_lifecycle() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.transition_to(TradingPhase::Closed)
+            .expect("continuous can close");
+        book.transition_to(TradingPhase::PreOpen)
+            .expect("next session");
+
+        // Pre-open: limit orders accumulate, market orders are rejected
+        let buy = book
+            .add_limit_order(OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(4))
+            .expect("invalid order");
+        assert!(buy.fills.is_empty());
+        assert_eq!(
+            book.execute_market_order(OrderSide::Buy, dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::PreOpen))
+        );
+        assert!(book.indicative_equilibrium().is_some());
+        assert_eq!(
+            book.uncross(),
+            Err(BookError::NotAllowedInPhase(TradingPhase::PreOpen))
+        );
+
+        // Opening call
+        book.transition_to(TradingPhase::Auction)
+            .expect("pre-open can call");
+        let result = book.uncross().expect("book is in an auction");
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+
+        // Halted: cancels only
+        book.transition_to(TradingPhase::Halted)
+            .expect("continuous can halt");
+        assert_eq!(
+            book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Halted))
+        );
+        assert_eq!(
+            book.simulate_limit_order(OrderSide::Buy, dec!(99.00), dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Halted))
+        );
+        book.cancel_limit_order(buy.order_id)
+            .expect("cancels are accepted while halted");
+
+        // Resume through an auction, then close
+        book.transition_to(TradingPhase::Auction)
+            .expect("halted reopens with a call");
+        book.uncross().expect("book is in an auction");
+        book.transition_to(TradingPhase::Closed)
+            .expect("continuous can close");
+        assert_eq!(
+            book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Closed))
+        );
+
+        let phases: Vec<(TradingPhase, TradingPhase)> = book
+            .drain_events()
+            .into_iter()
+            .map(|event| match event {
+                BookEvent::PhaseChanged { from, to } => (from, to),
+                _ => unreachable!("only phase changes happened"),
+            })
+            .collect();
+        use TradingPhase::*;
+        assert_eq!(
+            phases,
+            vec![
+                (Continuous, Closed),
+                (Closed, PreOpen),
+                (PreOpen, Auction),
+                (Auction, Continuous),
+                (Continuous, Halted),
+                (Halted, Auction),
+                (Auction, Continuous),
+                (Continuous, Closed),
+            ]
+        );
+        assert!(book.drain_events().is_empty());
+    }
+
+    #[test]
+    fn test_transitions() {
+        use TradingPhase::*;
+        assert!(Continuous.can_transition_to(Halted));
+        assert!(!Auction.can_transition_to(Continuous)); // Only by uncrossing
+        assert!(!Halted.can_transition_to(Continuous));
+        assert!(!Closed.can_transition_to(Continuous));
+        assert!(!PreOpen.can_transition_to(PreOpen));
+    }
+}
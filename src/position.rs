@@ -0,0 +1,275 @@
+use crate::fees::FeeCurrency;
+use crate::order::{Fill, OrderSide, OwnerId};
+use crate::order_book::OrderBook;
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Which book price open positions are marked at.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum MarkPrice {
+    /// Midpoint of the best bid and ask
+    #[default]
+    Mid,
+    /// The book's reference price, the last trade unless set explicitly
+    Last,
+}
+
+/// One account's position, built from its fills.
+///
+/// # Fields
+/// * `quantity` - Net quantity, positive when long
+/// * `average_price` - Average entry price of the open quantity, zero when flat
+/// * `realized_pnl` - Profit locked in by reducing or closing the position, before fees
+/// * `quote_fees` - Fees charged in the quote currency. Negative for net rebates.
+/// * `base_fees` - Fees charged in the base currency. Negative for net rebates.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Position {
+    pub quantity: Decimal,
+    pub average_price: Decimal,
+    pub realized_pnl: Decimal,
+    pub quote_fees: Decimal,
+    pub base_fees: Decimal,
+}
+
+impl Position {
+    /// Profit on the open quantity if it were closed at `mark_price`
+    pub fn unrealized_pnl(&self, mark_price: Decimal) -> Decimal {
+        (mark_price - self.average_price) * self.quantity
+    }
+
+    fn charge(&mut self, fee: Decimal, currency: FeeCurrency) {
+        match currency {
+            FeeCurrency::Quote => self.quote_fees += fee,
+            FeeCurrency::Base => self.base_fees += fee,
+        }
+    }
+
+    // Trade `quantity` (negative for a sale) at `price`
+    fn trade(&mut self, quantity: Decimal, price: Decimal) {
+        let same_direction = self.quantity.is_sign_positive() == quantity.is_sign_positive();
+        if self.quantity.is_zero() || same_direction {
+            let open = self.quantity.abs();
+            self.average_price =
+                (self.average_price * open + price * quantity.abs()) / (open + quantity.abs());
+            self.quantity += quantity;
+            return;
+        }
+
+        // Reducing, closing or flipping the position
+        let closed = quantity.abs().min(self.quantity.abs());
+        let direction = if self.quantity > Decimal::ZERO {
+            Decimal::ONE
+        } else {
+            Decimal::NEGATIVE_ONE
+        };
+        self.realized_pnl += (price - self.average_price) * closed * direction;
+        self.quantity += quantity;
+
+        if self.quantity.is_zero() {
+            self.average_price = Decimal::ZERO;
+        } else if quantity.abs() > closed {
+            self.average_price = price; // Flipped, the rest opened at this price
+        }
+    }
+}
+
+/// Per-account positions and PnL, kept up to date from fills.
+///
+/// Both sides of each fill are booked to their owners, fills without an
+/// owner are skipped. Every book keeps one for the fills it executes, see
+/// [`OrderBook::positions`], and its risk checks use the same positions. A
+/// keeper of your own can follow fills from elsewhere, such as a replica.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{MarkPrice, OrderBook, OrderSide};
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+///
+/// book.add_limit_order_for(1, OrderSide::Sell, dec!(100.00), dec!(10)).unwrap();
+/// book.add_limit_order_for(2, OrderSide::Buy, dec!(100.00), dec!(4)).unwrap();
+///
+/// assert_eq!(book.positions().position(1).quantity, dec!(-4));
+/// assert_eq!(book.positions().position(2).average_price, dec!(100.00));
+///
+/// // Marked at the mid of 99.00 / 100.00
+/// book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(1)).unwrap();
+/// let positions = book.positions();
+/// assert_eq!(positions.unrealized_pnl(2, &book, MarkPrice::Mid), Some(dec!(-2)));
+/// assert_eq!(positions.unrealized_pnl(2, &book, MarkPrice::Last), Some(dec!(0)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PositionKeeper {
+    positions: HashMap<OwnerId, Position>,
+}
+
+impl PositionKeeper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Book both sides of a fill
+    pub fn apply_fill(&mut self, fill: &Fill) {
+        let bought = match fill.taker_side {
+            OrderSide::Buy => fill.quantity,
+            OrderSide::Sell => -fill.quantity,
+        };
+
+        if let Some(owner) = fill.taker_owner {
+            let position = self.positions.entry(owner).or_default();
+            position.trade(bought, fill.price);
+            position.charge(fill.taker_fee, fill.fee_currency);
+        }
+        if let Some(owner) = fill.maker_owner {
+            let position = self.positions.entry(owner).or_default();
+            position.trade(-bought, fill.price);
+            position.charge(fill.maker_fee, fill.fee_currency);
+        }
+    }
+
+    pub fn apply_fills(&mut self, fills: &[Fill]) {
+        for fill in fills {
+            self.apply_fill(fill);
+        }
+    }
+
+    /// The position of `owner`, flat if it never traded
+    pub fn position(&self, owner: OwnerId) -> Position {
+        self.positions.get(&owner).copied().unwrap_or_default()
+    }
+
+    /// Every account that traded, in no particular order
+    pub fn positions(&self) -> impl Iterator<Item = (OwnerId, &Position)> {
+        self.positions
+            .iter()
+            .map(|(owner, position)| (*owner, position))
+    }
+
+    /// Unrealized PnL of `owner` marked at the book's mid or last price.
+    /// None if the book has no such price.
+    pub fn unrealized_pnl(
+        &self,
+        owner: OwnerId,
+        book: &OrderBook,
+        mark: MarkPrice,
+    ) -> Option<Decimal> {
+        let mark_price = match mark {
+            MarkPrice::Mid => book.mid_price(),
+            MarkPrice::Last => book.reference_price(),
+        }?;
+        Some(self.position(owner).unrealized_pnl(mark_price))
+    }
+}
+
+impl OrderBook {
+    /// Positions and PnL of every account that traded in this book
+    pub fn positions(&self) -> &PositionKeeper {
+        &self.positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fees::FeeSchedule;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_position_average_price_and_pnl() {
+        let mut position = Position::default();
+        position.trade(dec!(10), dec!(100));
+        position.trade(dec!(10), dec!(110));
+        assert_eq!(position.average_price, dec!(105));
+
+        // Reduce: realize 5 * (120 - 105)
+        position.trade(dec!(-5), dec!(120));
+        assert_eq!(position.quantity, dec!(15));
+        assert_eq!(position.average_price, dec!(105));
+        assert_eq!(position.realized_pnl, dec!(75));
+        assert_eq!(position.unrealized_pnl(dec!(100)), dec!(-75));
+
+        // Flip short: close 15 at 100, open 5 short at 100
+        position.trade(dec!(-20), dec!(100));
+        assert_eq!(position.quantity, dec!(-5));
+        assert_eq!(position.average_price, dec!(100));
+        assert_eq!(position.realized_pnl, dec!(0));
+        assert_eq!(position.unrealized_pnl(dec!(90)), dec!(50));
+
+        position.trade(dec!(5), dec!(90));
+        assert_eq!(position.quantity, dec!(0));
+        assert_eq!(position.average_price, dec!(0));
+        assert_eq!(position.realized_pnl, dec!(50));
+    }
+
+    #[test]
+    fn test_keeper_books_both_sides() {
+        let fees = FeeSchedule::new(dec!(-0.001), dec!(0.002)).expect("valid rates");
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_fee_schedule(fees);
+        let mut positions = PositionKeeper::new();
+
+        book.add_limit_order_for(1, OrderSide::Buy, dec!(100.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(5))
+            .expect("invalid order");
+        let report = book
+            .add_limit_order_for(2, OrderSide::Sell, dec!(99.00), dec!(8))
+            .expect("invalid order");
+        positions.apply_fills(&report.fills);
+
+        let buyer = positions.position(1);
+        assert_eq!(buyer.quantity, dec!(5));
+        assert_eq!(buyer.quote_fees, dec!(-0.5));
+        let seller = positions.position(2);
+        assert_eq!(seller.quantity, dec!(-8));
+        assert_eq!(seller.average_price, dec!(99.625));
+        assert_eq!(seller.quote_fees, dec!(1.594));
+        assert_eq!(seller.base_fees, dec!(0));
+        assert_eq!(positions.positions().count(), 2);
+
+        // The book kept the same positions, and checks risk against them
+        assert_eq!(book.positions().position(1), buyer);
+        assert_eq!(book.positions().position(2), seller);
+        assert_eq!(book.account_exposure(2).position, dec!(-8));
+
+        // Last trade at 99.00, no bids left for a mid
+        assert_eq!(
+            positions.unrealized_pnl(1, &book, MarkPrice::Last),
+            Some(dec!(-5))
+        );
+        assert_eq!(positions.unrealized_pnl(1, &book, MarkPrice::Mid), None);
+    }
+
+    #[test]
+    fn test_book_positions_from_uncross_and_base_fees() {
+        let fees = FeeSchedule::new(dec!(0), dec!(0.01))
+            .expect("valid rates")
+            .with_currency(FeeCurrency::Base);
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_fee_schedule(fees);
+        book.start_auction()
+            .expect("continuous can enter an auction");
+        book.add_limit_order_for(1, OrderSide::Buy, dec!(101.00), dec!(10))
+            .expect("invalid order");
+        book.add_limit_order_for(2, OrderSide::Sell, dec!(99.00), dec!(10))
+            .expect("invalid order");
+        let result = book.uncross().expect("book is in an auction");
+        let price = result.equilibrium.expect("the book crosses").price;
+
+        // Both sides trade at the clearing price, not their own limits
+        let buyer = book.positions().position(1);
+        assert_eq!(buyer.quantity, dec!(10));
+        assert_eq!(buyer.average_price, price);
+        let seller = book.positions().position(2);
+        assert_eq!(seller.quantity, dec!(-10));
+        assert_eq!(seller.average_price, price);
+
+        // The later order took, its fee is in the base currency
+        assert_eq!(seller.base_fees, dec!(0.1));
+        assert_eq!(seller.quote_fees, dec!(0));
+        assert_eq!(buyer.base_fees, dec!(0));
+    }
+}
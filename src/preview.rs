@@ -0,0 +1,232 @@
+use crate::error::BookError;
+use crate::order::{MarketOrderOptions, OrderSide};
+use crate::order_book::{MatchStop, OrderBook};
+
+use rust_decimal::Decimal;
+
+/// The expected cost of sweeping the book with a market order, computed
+/// without touching the book.
+///
+/// # Fields
+/// * `requested_quantity` - The quantity the preview was run for
+/// * `filled_quantity` - How much the book could fill (less than requested if liquidity runs out)
+/// * `notional` - Sum of price * quantity over all would-be fills
+/// * `average_price` - Volume weighted average fill price (VWAP)
+/// * `worst_price` - Price of the last level touched
+/// * `levels_consumed` - Number of price levels touched, including a partially consumed last level
+/// * `slippage` - Distance of the average price from mid, positive when worse than mid
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SweepPreview {
+    pub requested_quantity: Decimal,
+    pub filled_quantity: Decimal,
+    pub notional: Decimal,
+    pub average_price: Option<Decimal>, // None if nothing would fill
+    pub worst_price: Option<Decimal>,
+    pub levels_consumed: usize,
+    pub slippage: Option<Decimal>, // None without a two-sided book
+    pub(crate) stop: MatchStop,
+}
+
+impl SweepPreview {
+    /// Whether the book holds enough liquidity to fill the whole quantity
+    pub fn is_complete(&self) -> bool {
+        self.filled_quantity == self.requested_quantity
+    }
+
+    /// Quantity that would be left unfilled
+    pub fn unfilled_quantity(&self) -> Decimal {
+        self.requested_quantity - self.filled_quantity
+    }
+
+    /// Why the sweep would stop short, the `reject_reason` a market order
+    /// allowing partial fills would report. None if it fills completely.
+    pub fn stop_reason(&self) -> Option<BookError> {
+        (!self.is_complete()).then(|| self.stop.cancel_reason())
+    }
+}
+
+impl OrderBook {
+    /// Preview the fills a market order of `quantity` would receive.
+    ///
+    /// Plans the order exactly like `execute_market_order` without
+    /// touching the book: the same price priority, allocation, instrument
+    /// checks and trading phase, and it stops at the same price band or
+    /// volatility breaker limit execution would stop at.
+    ///
+    /// Unlike `execute_market_order` falling short is not an error, the
+    /// preview reports how much could be filled and `stop_reason` why the
+    /// rest could not.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(10)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(102.00), dec!(10)).unwrap();
+    ///
+    /// let preview = book.preview_market_order(OrderSide::Buy, dec!(15)).unwrap();
+    /// assert_eq!(preview.average_price, Some(dec!(101.33333333333333333333333333)));
+    /// assert_eq!(preview.worst_price, Some(dec!(102.00)));
+    /// assert_eq!(preview.levels_consumed, 2);
+    ///
+    /// // Nothing was executed
+    /// assert_eq!(book.best_ask_volume(), Some(dec!(10)));
+    /// ```
+    pub fn preview_market_order(
+        &self,
+        side: OrderSide,
+        quantity: Decimal,
+    ) -> Result<SweepPreview, BookError> {
+        let options = MarketOrderOptions {
+            allow_partial: true,
+            max_slippage: None,
+        };
+        let plan = self.plan_market_order(self.next_id, None, side, quantity, options)?;
+        let filled_quantity = plan.filled_quantity;
+        let notional = plan.notional;
+        let worst_price = plan.fills.last().map(|fill| fill.price);
+
+        let average_price = if filled_quantity > Decimal::ZERO {
+            Some(notional / filled_quantity)
+        } else {
+            None
+        };
+
+        let slippage = match (average_price, self.mid_price()) {
+            (Some(average), Some(mid)) => Some(match side {
+                OrderSide::Buy => average - mid,
+                OrderSide::Sell => mid - average,
+            }),
+            _ => None,
+        };
+
+        Ok(SweepPreview {
+            requested_quantity: quantity,
+            filled_quantity,
+            notional,
+            average_price,
+            worst_price,
+            levels_consumed: plan.levels_consumed(),
+            slippage,
+            stop: plan.stop,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bands::PriceBand;
+    use crate::event::BookEvent;
+    use crate::phase::TradingPhase;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_preview_matches_execution() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(20))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.50), dec!(20))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(20))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(100.50), dec!(5))
+            .expect("invalid order");
+
+        let preview = book
+            .preview_market_order(OrderSide::Sell, dec!(30))
+            .expect("valid preview");
+        assert!(preview.is_complete());
+        assert_eq!(preview.notional, dec!(2995.00));
+        assert_eq!(
+            preview.average_price,
+            Some(dec!(99.83333333333333333333333333))
+        );
+        assert_eq!(preview.worst_price, Some(dec!(99.50)));
+        assert_eq!(preview.levels_consumed, 2);
+        // Mid is 100.25
+        assert_eq!(preview.slippage, Some(dec!(0.41666666666666666666666667)));
+
+        // Executing produces the previewed cost
+        let report = book
+            .execute_market_order(OrderSide::Sell, dec!(30))
+            .expect("Market order should execute");
+        assert_eq!(report.filled_notional, preview.notional);
+    }
+
+    #[test]
+    fn test_preview_insufficient_liquidity() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(10))
+            .expect("invalid order");
+
+        let preview = book
+            .preview_market_order(OrderSide::Buy, dec!(25))
+            .expect("valid preview");
+        assert!(!preview.is_complete());
+        assert_eq!(preview.unfilled_quantity(), dec!(15));
+        assert_eq!(preview.average_price, Some(dec!(101.00)));
+        assert_eq!(preview.slippage, None); // One-sided book has no mid
+
+        let preview = book
+            .preview_market_order(OrderSide::Sell, dec!(5))
+            .expect("valid preview");
+        assert_eq!(preview.filled_quantity, dec!(0));
+        assert_eq!(preview.average_price, None);
+        assert_eq!(preview.levels_consumed, 0);
+
+        assert!(book.preview_market_order(OrderSide::Buy, dec!(0)).is_err());
+    }
+
+    #[test]
+    fn test_preview_stops_where_execution_does() {
+        let band = PriceBand::new(dec!(0.05)).expect("valid width");
+        let mut book = OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_price_band(band);
+        book.set_reference_price(dec!(100.00))
+            .expect("positive price");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(104.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(120.00), dec!(5))
+            .expect("invalid order");
+
+        // The raw book holds 15, but the band stops the sweep at 105.00
+        let preview = book
+            .preview_market_order(OrderSide::Buy, dec!(15))
+            .expect("valid preview");
+        assert_eq!(preview.stop, MatchStop::PriceBand(dec!(105.00)));
+        assert_eq!(preview.filled_quantity, dec!(10));
+        assert_eq!(preview.worst_price, Some(dec!(104.00)));
+        assert_eq!(preview.stop_reason(), Some(BookError::PriceBandReached));
+
+        let options = MarketOrderOptions {
+            allow_partial: true,
+            max_slippage: None,
+        };
+        let report = book
+            .execute_market_order_with(OrderSide::Buy, dec!(15), options)
+            .expect("partial fills allowed");
+        assert_eq!(report.filled_quantity, preview.filled_quantity);
+        assert_eq!(report.filled_notional, preview.notional);
+        assert_eq!(report.reject_reason, preview.stop_reason());
+        assert_eq!(
+            book.drain_events()[0],
+            BookEvent::PriceBandReached {
+                side: OrderSide::Buy,
+                limit: dec!(105.00),
+            }
+        );
+
+        // Halted at the band, so the preview is rejected like the order
+        assert_eq!(book.phase(), TradingPhase::Halted);
+        assert_eq!(
+            book.preview_market_order(OrderSide::Buy, dec!(1)),
+            Err(BookError::NotAllowedInPhase(TradingPhase::Halted))
+        );
+    }
+}
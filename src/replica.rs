@@ -0,0 +1,405 @@
+use crate::error::BookError;
+use crate::order::OrderSide;
+use crate::ticks::{Tick, TickTable};
+
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+
+/// A single price level change carried by an incremental depth message.
+///
+/// A quantity of zero removes the level, any other quantity replaces the
+/// aggregate size resting at that price.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct LevelUpdate {
+    pub side: OrderSide,
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// The result of applying an incremental update to a [`ReplicaBook`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DeltaOutcome {
+    /// The update was the next message in sequence and was applied.
+    Applied,
+    /// The update is older than the current book state (duplicate or
+    /// out-of-order delivery) and was ignored.
+    OutOfOrder { last: u64, received: u64 },
+    /// One or more messages were missed. The book is now stale.
+    Gap { expected: u64, received: u64 },
+    /// The book is stale and is waiting for a resync snapshot.
+    AwaitingSnapshot,
+}
+
+/// A market-data replica of an external order book, rebuilt from a snapshot
+/// followed by sequenced level-by-level depth updates.
+///
+/// Unlike [`OrderBook`](crate::OrderBook) the replica only knows aggregate
+/// size per price level, so each `Tick` maps directly to a quantity.
+///
+/// # Sequencing
+/// - The book starts stale and becomes live on the first snapshot
+/// - Each delta must carry the sequence immediately after the last applied one
+/// - Deltas at or below the current sequence are ignored as out-of-order
+/// - A jump in sequence marks the book stale until the next snapshot
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{DeltaOutcome, LevelUpdate, OrderSide, ReplicaBook};
+/// let mut replica = ReplicaBook::new(dec!(0.01)).unwrap();
+/// assert!(replica.is_stale());
+///
+/// replica
+///     .apply_snapshot(100, &[(dec!(99.99), dec!(5))], &[(dec!(100.01), dec!(3))])
+///     .unwrap();
+/// assert!(!replica.is_stale());
+///
+/// let update = LevelUpdate { side: OrderSide::Sell, price: dec!(100.01), quantity: dec!(0) };
+/// assert_eq!(replica.apply_delta(101, &[update]).unwrap(), DeltaOutcome::Applied);
+/// assert_eq!(replica.best_ask(), None);
+///
+/// // Message 102 was lost
+/// assert_eq!(
+///     replica.apply_delta(103, &[]).unwrap(),
+///     DeltaOutcome::Gap { expected: 102, received: 103 }
+/// );
+/// assert!(replica.is_stale());
+/// ```
+pub struct ReplicaBook {
+    pub(crate) tick_table: TickTable,
+    pub(crate) bids: BTreeMap<Tick, Decimal>,
+    pub(crate) asks: BTreeMap<Tick, Decimal>,
+    pub(crate) sequence: Option<u64>, // Sequence of the last applied message
+    pub(crate) stale: bool,
+}
+
+impl ReplicaBook {
+    pub fn new(tick_size: Decimal) -> Result<Self, BookError> {
+        Self::from_tick_table(TickTable::fixed(tick_size))
+    }
+
+    /// Create a replica for a venue with price-banded tick sizes
+    pub fn from_tick_table(tick_table: TickTable) -> Result<Self, BookError> {
+        tick_table.validate()?;
+
+        Ok(Self {
+            tick_table,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            sequence: None,
+            stale: true, // Nothing to trust until the first snapshot
+        })
+    }
+
+    /// Replace the whole book with a full depth snapshot.
+    ///
+    /// Levels are given as `(price, quantity)` pairs. A snapshot always
+    /// clears the stale flag and resets the expected sequence, so it is
+    /// also the way to resync after a gap.
+    ///
+    /// # Errors
+    /// Returns an error, leaving the book stale, if any price is not
+    /// positive and aligned to the tick size or any quantity is not positive.
+    pub fn apply_snapshot(
+        &mut self,
+        sequence: u64,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> Result<(), BookError> {
+        self.bids.clear();
+        self.asks.clear();
+        self.sequence = None;
+        self.stale = true;
+
+        let mut new_bids = BTreeMap::new();
+        for &(price, quantity) in bids {
+            if quantity <= Decimal::ZERO {
+                return Err(BookError::InvalidQuantity);
+            }
+            new_bids.insert(self.tick(price)?, quantity);
+        }
+
+        let mut new_asks = BTreeMap::new();
+        for &(price, quantity) in asks {
+            if quantity <= Decimal::ZERO {
+                return Err(BookError::InvalidQuantity);
+            }
+            new_asks.insert(self.tick(price)?, quantity);
+        }
+
+        self.bids = new_bids;
+        self.asks = new_asks;
+        self.sequence = Some(sequence);
+        self.stale = false;
+        Ok(())
+    }
+
+    /// Apply an incremental depth message.
+    ///
+    /// All updates in the message are validated before any is applied, so
+    /// a message either applies in full or not at all.
+    ///
+    /// # Errors
+    /// Returns an error and marks the book stale if the message contains a
+    /// negative quantity or a price that is not aligned to the tick size.
+    pub fn apply_delta(
+        &mut self,
+        sequence: u64,
+        updates: &[LevelUpdate],
+    ) -> Result<DeltaOutcome, BookError> {
+        if self.stale {
+            return Ok(DeltaOutcome::AwaitingSnapshot);
+        }
+
+        // A live book always has a sequence
+        let last = self.sequence.expect("live replica without sequence");
+        if sequence <= last {
+            return Ok(DeltaOutcome::OutOfOrder {
+                last,
+                received: sequence,
+            });
+        }
+
+        let expected = last + 1;
+        if sequence != expected {
+            self.stale = true;
+            return Ok(DeltaOutcome::Gap {
+                expected,
+                received: sequence,
+            });
+        }
+
+        let mut ticks = Vec::with_capacity(updates.len());
+        for update in updates {
+            if update.quantity < Decimal::ZERO {
+                self.stale = true;
+                return Err(BookError::InvalidQuantity);
+            }
+            match self.tick(update.price) {
+                Ok(tick) => ticks.push(tick),
+                Err(err) => {
+                    self.stale = true;
+                    return Err(err);
+                }
+            }
+        }
+
+        for (update, tick) in updates.iter().zip(ticks) {
+            let book_side = match update.side {
+                OrderSide::Buy => &mut self.bids,
+                OrderSide::Sell => &mut self.asks,
+            };
+
+            if update.quantity == Decimal::ZERO {
+                book_side.remove(&tick);
+            } else {
+                book_side.insert(tick, update.quantity);
+            }
+        }
+
+        self.sequence = Some(sequence);
+        Ok(DeltaOutcome::Applied)
+    }
+
+    /// Mark the book stale, e.g. after a feed disconnect.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    // Feed prices must already sit on a tick, rounding would hide a bad feed
+    fn tick(&self, price: Decimal) -> Result<Tick, BookError> {
+        let tick = Tick::in_table(price, &self.tick_table)?;
+        if tick.level() != price {
+            return Err(BookError::PriceNotAligned);
+        }
+        Ok(tick)
+    }
+
+    /// Helpers
+    /// Whether the book is waiting for a snapshot and should not be trusted
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Sequence number of the last applied snapshot or delta
+    pub fn sequence(&self) -> Option<u64> {
+        self.sequence
+    }
+
+    /// Get the best (highest) bid price if any bids exist
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.last_key_value().map(|(tick, _)| tick.level())
+    }
+
+    /// Get the best (lowest) ask price if any asks exist
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.first_key_value().map(|(tick, _)| tick.level())
+    }
+
+    /// Get the quantity resting at a price level, if the level exists
+    pub fn level_quantity(&self, side: OrderSide, price: Decimal) -> Option<Decimal> {
+        let tick = Tick::in_table(price, &self.tick_table).ok()?;
+        match side {
+            OrderSide::Buy => self.bids.get(&tick).copied(),
+            OrderSide::Sell => self.asks.get(&tick).copied(),
+        }
+    }
+
+    /// Get up to `depth` bid levels as `(price, quantity)`, best first
+    pub fn bid_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.bids
+            .iter()
+            .rev()
+            .take(depth)
+            .map(|(tick, quantity)| (tick.level(), *quantity))
+            .collect()
+    }
+
+    /// Get up to `depth` ask levels as `(price, quantity)`, best first
+    pub fn ask_levels(&self, depth: usize) -> Vec<(Decimal, Decimal)> {
+        self.asks
+            .iter()
+            .take(depth)
+            .map(|(tick, quantity)| (tick.level(), *quantity))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn update(side: OrderSide, price: Decimal, quantity: Decimal) -> LevelUpdate {
+        LevelUpdate {
+            side,
+            price,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_and_deltas() {
+        let mut replica = ReplicaBook::new(dec!(0.01)).expect("tick spacing must be positive");
+
+        // Deltas before the first snapshot are dropped
+        assert_eq!(
+            replica
+                .apply_delta(1, &[update(OrderSide::Buy, dec!(99.00), dec!(1))])
+                .expect("valid delta"),
+            DeltaOutcome::AwaitingSnapshot
+        );
+        assert!(replica.bids.is_empty());
+
+        replica
+            .apply_snapshot(
+                10,
+                &[(dec!(99.00), dec!(5)), (dec!(98.00), dec!(7))],
+                &[(dec!(101.00), dec!(4))],
+            )
+            .expect("valid snapshot");
+        assert_eq!(replica.best_bid(), Some(dec!(99.00)));
+        assert_eq!(replica.best_ask(), Some(dec!(101.00)));
+
+        // Insert, replace and delete levels
+        let outcome = replica
+            .apply_delta(
+                11,
+                &[
+                    update(OrderSide::Buy, dec!(99.50), dec!(2)),
+                    update(OrderSide::Buy, dec!(98.00), dec!(3)),
+                    update(OrderSide::Sell, dec!(101.00), dec!(0)),
+                    update(OrderSide::Sell, dec!(102.00), dec!(6)),
+                ],
+            )
+            .expect("valid delta");
+        assert_eq!(outcome, DeltaOutcome::Applied);
+        assert_eq!(replica.sequence(), Some(11));
+        assert_eq!(
+            replica.bid_levels(10),
+            vec![
+                (dec!(99.50), dec!(2)),
+                (dec!(99.00), dec!(5)),
+                (dec!(98.00), dec!(3))
+            ]
+        );
+        assert_eq!(replica.ask_levels(10), vec![(dec!(102.00), dec!(6))]);
+
+        // Replayed message is ignored
+        let outcome = replica
+            .apply_delta(11, &[update(OrderSide::Buy, dec!(97.00), dec!(1))])
+            .expect("valid delta");
+        assert_eq!(
+            outcome,
+            DeltaOutcome::OutOfOrder {
+                last: 11,
+                received: 11
+            }
+        );
+        assert_eq!(replica.level_quantity(OrderSide::Buy, dec!(97.00)), None);
+    }
+
+    #[test]
+    fn test_gap_marks_stale_until_snapshot() {
+        let mut replica = ReplicaBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        replica
+            .apply_snapshot(1, &[(dec!(99.00), dec!(5))], &[])
+            .expect("valid snapshot");
+
+        let outcome = replica
+            .apply_delta(3, &[update(OrderSide::Buy, dec!(99.00), dec!(1))])
+            .expect("valid delta");
+        assert_eq!(
+            outcome,
+            DeltaOutcome::Gap {
+                expected: 2,
+                received: 3
+            }
+        );
+        assert!(replica.is_stale());
+        assert_eq!(
+            replica.level_quantity(OrderSide::Buy, dec!(99.00)),
+            Some(dec!(5))
+        );
+
+        // Everything is dropped until a resync snapshot arrives
+        assert_eq!(
+            replica.apply_delta(2, &[]).expect("valid delta"),
+            DeltaOutcome::AwaitingSnapshot
+        );
+
+        replica
+            .apply_snapshot(5, &[(dec!(98.00), dec!(2))], &[])
+            .expect("valid snapshot");
+        assert!(!replica.is_stale());
+        assert_eq!(replica.best_bid(), Some(dec!(98.00)));
+        assert_eq!(
+            replica.apply_delta(6, &[]).expect("valid delta"),
+            DeltaOutcome::Applied
+        );
+    }
+
+    #[test]
+    fn test_invalid_delta_is_atomic() {
+        let mut replica = ReplicaBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        replica
+            .apply_snapshot(1, &[(dec!(99.00), dec!(5))], &[])
+            .expect("valid snapshot");
+
+        // Off-tick price in the second update rejects the whole message
+        let result = replica.apply_delta(
+            2,
+            &[
+                update(OrderSide::Buy, dec!(99.00), dec!(0)),
+                update(OrderSide::Buy, dec!(98.005), dec!(1)),
+            ],
+        );
+        assert!(result.is_err());
+        assert!(replica.is_stale());
+        assert_eq!(
+            replica.level_quantity(OrderSide::Buy, dec!(99.00)),
+            Some(dec!(5))
+        );
+        assert_eq!(replica.sequence(), Some(1));
+    }
+}
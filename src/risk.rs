@@ -0,0 +1,497 @@
+use crate::error::BookError;
+use crate::order::{Order, OrderSide, OwnerId};
+use crate::order_book::OrderBook;
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Pre-trade limits for one account, checked on every order submitted on
+/// its behalf (`add_limit_order_for`, `execute_market_order_for`, ...)
+/// before the order gets an id or reaches the matching loop.
+///
+/// Every limit is optional.
+///
+/// # Fields
+/// * `max_order_quantity` - Largest quantity of a single order
+/// * `max_order_notional` - Largest price * quantity of a single order
+/// * `max_open_orders` - Most orders the account may have resting at once
+/// * `max_position` - Largest absolute net position the account may reach,
+///   assuming the new order and every resting order on the same side fill
+/// * `max_price_deviation` - Furthest a limit price, or the worst fill of a
+///   market order, may be from the reference price (or the mid if there is
+///   none), as a fraction of it
+///
+/// Market orders have no price, so they are checked against the fills they
+/// would get: their notional is the sum over every level they would sweep,
+/// the price deviation is that of the last level they reach, and
+/// notional-sized orders are checked with the quantity they would fill.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{BookError, OrderBook, OrderSide, RiskLimits, RiskViolation};
+/// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+/// book.set_reference_price(dec!(100.00)).unwrap();
+/// let limits = RiskLimits::new()
+///     .with_max_position(dec!(10))
+///     .with_max_price_deviation(dec!(0.1));
+/// book.set_risk_limits(1, limits).unwrap();
+///
+/// book.add_limit_order_for(1, OrderSide::Buy, dec!(99.00), dec!(6)).unwrap();
+/// assert_eq!(
+///     book.add_limit_order_for(1, OrderSide::Buy, dec!(98.00), dec!(6)),
+///     Err(BookError::RiskLimitBreached(RiskViolation::Position {
+///         limit: dec!(10),
+///         projected: dec!(12),
+///     }))
+/// );
+/// assert!(matches!(
+///     book.add_limit_order_for(1, OrderSide::Sell, dec!(1000.00), dec!(1)),
+///     Err(BookError::RiskLimitBreached(RiskViolation::PriceDeviation { .. }))
+/// ));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct RiskLimits {
+    pub max_order_quantity: Option<Decimal>,
+    pub max_order_notional: Option<Decimal>,
+    pub max_open_orders: Option<usize>,
+    pub max_position: Option<Decimal>,
+    pub max_price_deviation: Option<Decimal>,
+}
+
+impl RiskLimits {
+    /// Limits that allow everything
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_order_quantity(mut self, max_order_quantity: Decimal) -> Self {
+        self.max_order_quantity = Some(max_order_quantity);
+        self
+    }
+
+    pub fn with_max_order_notional(mut self, max_order_notional: Decimal) -> Self {
+        self.max_order_notional = Some(max_order_notional);
+        self
+    }
+
+    pub fn with_max_open_orders(mut self, max_open_orders: usize) -> Self {
+        self.max_open_orders = Some(max_open_orders);
+        self
+    }
+
+    pub fn with_max_position(mut self, max_position: Decimal) -> Self {
+        self.max_position = Some(max_position);
+        self
+    }
+
+    pub fn with_max_price_deviation(mut self, max_price_deviation: Decimal) -> Self {
+        self.max_price_deviation = Some(max_price_deviation);
+        self
+    }
+
+    /// Check the limits themselves make sense.
+    pub fn validate(&self) -> Result<(), BookError> {
+        if self
+            .max_order_quantity
+            .is_some_and(|max| max <= Decimal::ZERO)
+        {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        if self
+            .max_order_notional
+            .is_some_and(|max| max <= Decimal::ZERO)
+        {
+            return Err(BookError::InvalidNotional);
+        }
+
+        // A zero position limit is allowed, it only lets an account go flat
+        if self.max_position.is_some_and(|max| max < Decimal::ZERO) {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        if self
+            .max_price_deviation
+            .is_some_and(|max| max <= Decimal::ZERO || max > Decimal::ONE)
+        {
+            return Err(BookError::InvalidPercentage);
+        }
+
+        Ok(())
+    }
+}
+
+/// Which pre-trade limit an order breached, carried by
+/// `BookError::RiskLimitBreached`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RiskViolation {
+    /// Order quantity above `max_order_quantity`
+    OrderQuantity { limit: Decimal },
+    /// Order notional above `max_order_notional`
+    OrderNotional { limit: Decimal },
+    /// The account already has `max_open_orders` resting
+    OpenOrders { limit: usize },
+    /// The absolute position would reach `projected`, above `max_position`
+    Position { limit: Decimal, projected: Decimal },
+    /// Limit price or worst market fill further than `max_price_deviation`
+    /// from `reference`
+    PriceDeviation { limit: Decimal, reference: Decimal },
+}
+
+/// What an account has working and filled in the book, the state its
+/// risk limits are checked against.
+///
+/// # Fields
+/// * `open_orders` - Orders resting in the book
+/// * `open_buy_quantity` - Unfilled quantity of the resting buys
+/// * `open_sell_quantity` - Unfilled quantity of the resting sells
+/// * `position` - Net filled quantity, positive when long. The same as the
+///   quantity of the account's [`Position`](crate::Position)
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct AccountExposure {
+    pub open_orders: usize,
+    pub open_buy_quantity: Decimal,
+    pub open_sell_quantity: Decimal,
+    pub position: Decimal,
+}
+
+impl AccountExposure {
+    fn open_quantity(&mut self, side: OrderSide) -> &mut Decimal {
+        match side {
+            OrderSide::Buy => &mut self.open_buy_quantity,
+            OrderSide::Sell => &mut self.open_sell_quantity,
+        }
+    }
+}
+
+// Track an owned order that started resting
+pub(crate) fn track_resting(accounts: &mut HashMap<OwnerId, AccountExposure>, order: &Order) {
+    let Some(owner) = order.owner else {
+        return;
+    };
+
+    let account = accounts.entry(owner).or_default();
+    account.open_orders += 1;
+    *account.open_quantity(order.order_side) += order.quantity;
+}
+
+// Track `quantity` of an owned resting order that filled or was cancelled.
+// `order` holds what is left of it.
+pub(crate) fn release_resting(
+    accounts: &mut HashMap<OwnerId, AccountExposure>,
+    order: &Order,
+    quantity: Decimal,
+) {
+    let Some(account) = order.owner.and_then(|owner| accounts.get_mut(&owner)) else {
+        return;
+    };
+
+    *account.open_quantity(order.order_side) -= quantity;
+    if order.quantity == Decimal::ZERO {
+        account.open_orders -= 1;
+    }
+}
+
+impl OrderBook {
+    /// Set the pre-trade limits of `owner`, replacing any earlier ones
+    pub fn set_risk_limits(&mut self, owner: OwnerId, limits: RiskLimits) -> Result<(), BookError> {
+        limits.validate()?;
+        self.risk_limits.insert(owner, limits);
+        Ok(())
+    }
+
+    pub fn risk_limits(&self, owner: OwnerId) -> Option<&RiskLimits> {
+        self.risk_limits.get(&owner)
+    }
+
+    /// Stop checking `owner`'s orders, returning the limits they had
+    pub fn remove_risk_limits(&mut self, owner: OwnerId) -> Option<RiskLimits> {
+        self.risk_limits.remove(&owner)
+    }
+
+    /// What `owner` has resting and filled in the book
+    pub fn account_exposure(&self, owner: OwnerId) -> AccountExposure {
+        let working = self.accounts.get(&owner).copied().unwrap_or_default();
+        AccountExposure {
+            position: self.positions.position(owner).quantity,
+            ..working
+        }
+    }
+
+    // Check an order for `owner` against the kill switch and its limits.
+    // `price` is the furthest the order may trade at: its limit price, or
+    // the worst planned fill of a market order (None if it fills nothing).
+    // Only orders that `rests` count towards the open orders.
+    pub(crate) fn check_risk(
+        &self,
+        owner: OwnerId,
+        side: OrderSide,
+        quantity: Decimal,
+        notional: Decimal,
+        price: Option<Decimal>,
+        rests: bool,
+    ) -> Result<(), BookError> {
+        if self.blocked_accounts.contains(&owner) {
+            return Err(BookError::AccountBlocked(owner));
+        }
+
+        let Some(limits) = self.risk_limits.get(&owner) else {
+            return Ok(());
+        };
+        let account = self.account_exposure(owner);
+        let breached = |violation| Err(BookError::RiskLimitBreached(violation));
+
+        if let Some(limit) = limits.max_order_quantity.filter(|max| quantity > *max) {
+            return breached(RiskViolation::OrderQuantity { limit });
+        }
+
+        if let Some(limit) = limits.max_order_notional.filter(|max| notional > *max) {
+            return breached(RiskViolation::OrderNotional { limit });
+        }
+
+        if let Some(limit) = limits.max_open_orders {
+            if rests && account.open_orders >= limit {
+                return breached(RiskViolation::OpenOrders { limit });
+            }
+        }
+
+        if let Some(limit) = limits.max_position {
+            let projected = match side {
+                OrderSide::Buy => account.position + account.open_buy_quantity + quantity,
+                OrderSide::Sell => account.position - account.open_sell_quantity - quantity,
+            }
+            .abs();
+            if projected > limit {
+                return breached(RiskViolation::Position { limit, projected });
+            }
+        }
+
+        let reference = self.reference_price.or_else(|| self.mid_price());
+        if let (Some(limit), Some(price), Some(reference)) =
+            (limits.max_price_deviation, price, reference)
+        {
+            if (price - reference).abs() > reference * limit {
+                return breached(RiskViolation::PriceDeviation { limit, reference });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::MarketOrderOptions;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_limits_validation() {
+        assert_eq!(
+            RiskLimits::new()
+                .with_max_order_quantity(dec!(0))
+                .validate(),
+            Err(BookError::InvalidQuantity)
+        );
+        assert_eq!(
+            RiskLimits::new()
+                .with_max_price_deviation(dec!(2))
+                .validate(),
+            Err(BookError::InvalidPercentage)
+        );
+        assert!(RiskLimits::new()
+            .with_max_position(dec!(0))
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_risk_checks_per_account() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        let limits = RiskLimits::new()
+            .with_max_order_quantity(dec!(50))
+            .with_max_order_notional(dec!(2000))
+            .with_max_open_orders(2);
+        book.set_risk_limits(1, limits).expect("valid limits");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(100))
+            .expect("invalid order");
+
+        let breach = |violation| Err(BookError::RiskLimitBreached(violation));
+        assert_eq!(
+            book.add_limit_order_for(1, OrderSide::Buy, dec!(90.00), dec!(51)),
+            breach(RiskViolation::OrderQuantity { limit: dec!(50) })
+        );
+        assert_eq!(
+            book.execute_market_order_for(
+                1,
+                OrderSide::Buy,
+                dec!(21),
+                MarketOrderOptions::default()
+            ),
+            breach(RiskViolation::OrderNotional { limit: dec!(2000) })
+        );
+        assert_eq!(
+            book.execute_notional_market_order_for(1, OrderSide::Buy, dec!(2500), dec!(1)),
+            breach(RiskViolation::OrderNotional { limit: dec!(2000) })
+        );
+
+        // Other accounts and anonymous orders are not limited
+        book.add_limit_order_for(2, OrderSide::Buy, dec!(90.00), dec!(51))
+            .expect("no limits for owner 2");
+
+        let first = book
+            .add_limit_order_for(1, OrderSide::Buy, dec!(90.00), dec!(1))
+            .expect("within limits");
+        book.add_limit_order_for(1, OrderSide::Buy, dec!(91.00), dec!(1))
+            .expect("within limits");
+        assert_eq!(
+            book.add_limit_order_for(1, OrderSide::Buy, dec!(92.00), dec!(1)),
+            breach(RiskViolation::OpenOrders { limit: 2 })
+        );
+        // Market orders never rest, so they don't count
+        book.execute_market_order_for(1, OrderSide::Buy, dec!(5), MarketOrderOptions::default())
+            .expect("within limits");
+
+        // A cancel frees a slot
+        book.cancel_limit_order(first.order_id)
+            .expect("order is resting");
+        book.add_limit_order_for(1, OrderSide::Buy, dec!(92.00), dec!(1))
+            .expect("within limits");
+        assert_eq!(
+            book.account_exposure(1),
+            AccountExposure {
+                open_orders: 2,
+                open_buy_quantity: dec!(2),
+                open_sell_quantity: dec!(0),
+                position: dec!(5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_exposure_follows_fills() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.set_risk_limits(1, RiskLimits::new().with_max_position(dec!(10)))
+            .expect("valid limits");
+
+        book.add_limit_order_for(1, OrderSide::Sell, dec!(100.00), dec!(8))
+            .expect("within limits");
+        book.execute_market_order(OrderSide::Buy, dec!(5))
+            .expect("enough liquidity");
+        let exposure = book.account_exposure(1);
+        assert_eq!(exposure.open_sell_quantity, dec!(3));
+        assert_eq!(exposure.position, dec!(-5));
+
+        // Short 5 with 3 more working: only 2 more may be sold
+        assert_eq!(
+            book.add_limit_order_for(1, OrderSide::Sell, dec!(101.00), dec!(3)),
+            Err(BookError::RiskLimitBreached(RiskViolation::Position {
+                limit: dec!(10),
+                projected: dec!(11),
+            }))
+        );
+        // Buying reduces the position
+        book.add_limit_order_for(1, OrderSide::Buy, dec!(99.00), dec!(15))
+            .expect("ends long 10 at most");
+
+        book.execute_market_order(OrderSide::Buy, dec!(3))
+            .expect("enough liquidity");
+        assert_eq!(
+            book.account_exposure(1),
+            AccountExposure {
+                open_orders: 1,
+                open_buy_quantity: dec!(15),
+                open_sell_quantity: dec!(0),
+                position: dec!(-8),
+            }
+        );
+    }
+
+    #[test]
+    fn test_market_risk_checks_every_swept_level() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.set_risk_limits(1, RiskLimits::new().with_max_order_notional(dec!(2000)))
+            .expect("valid limits");
+        book.set_risk_limits(2, RiskLimits::new().with_max_position(dec!(10)))
+            .expect("valid limits");
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(1))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(1000.00), dec!(100))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(10.00), dec!(1))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(1.00), dec!(100))
+            .expect("invalid order");
+
+        // 20 at the best ask would be 2000, but 19 of them fill at 1000
+        assert_eq!(
+            book.execute_market_order_for(
+                1,
+                OrderSide::Buy,
+                dec!(20),
+                MarketOrderOptions::default()
+            ),
+            Err(BookError::RiskLimitBreached(RiskViolation::OrderNotional {
+                limit: dec!(2000),
+            }))
+        );
+
+        // 100 at the best bid would be 10, but it sweeps to 91
+        assert_eq!(
+            book.execute_notional_market_order_for(2, OrderSide::Sell, dec!(100), dec!(1)),
+            Err(BookError::RiskLimitBreached(RiskViolation::Position {
+                limit: dec!(10),
+                projected: dec!(91),
+            }))
+        );
+        assert_eq!(book.best_ask(), Some(dec!(100.00)));
+        assert_eq!(book.best_bid(), Some(dec!(10.00)));
+    }
+
+    #[test]
+    fn test_market_sweep_checks_price_deviation() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.set_reference_price(dec!(100.00))
+            .expect("valid reference price");
+        book.set_risk_limits(1, RiskLimits::new().with_max_price_deviation(dec!(0.05)))
+            .expect("valid limits");
+        book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(110.00), dec!(5))
+            .expect("invalid order");
+
+        // Within 5% as long as it stays at 101.00
+        book.execute_market_order_for(1, OrderSide::Buy, dec!(2), MarketOrderOptions::default())
+            .expect("within limits");
+
+        // Sweeping into 110.00 goes 10% above the last trade at 101.00
+        assert_eq!(
+            book.execute_market_order_for(
+                1,
+                OrderSide::Buy,
+                dec!(5),
+                MarketOrderOptions::default()
+            ),
+            Err(BookError::RiskLimitBreached(
+                RiskViolation::PriceDeviation {
+                    limit: dec!(0.05),
+                    reference: dec!(101.00),
+                }
+            ))
+        );
+        assert_eq!(
+            book.execute_notional_market_order_for(1, OrderSide::Buy, dec!(1000), dec!(1)),
+            Err(BookError::RiskLimitBreached(
+                RiskViolation::PriceDeviation {
+                    limit: dec!(0.05),
+                    reference: dec!(101.00),
+                }
+            ))
+        );
+        assert_eq!(
+            book.ask_levels(5),
+            vec![(dec!(101.00), dec!(3)), (dec!(110.00), dec!(5))]
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use crate::error::BookError;
+use crate::event::BookEvent;
+use crate::order::{MarketOrderOptions, OrderId, OrderSide, OwnerId};
+use crate::order_book::OrderBook;
+use crate::phase::TradingPhase;
+
+use rust_decimal::Decimal;
+
+/// A conditional order waiting for the market to reach its stop price.
+///
+/// A buy stop triggers once the last trade price is at or above
+/// `stop_price`, a sell stop once it is at or below. It then executes as a
+/// market order for `quantity` that accepts partial fills.
+///
+/// # Fields
+/// * `id` - Id assigned when the stop was placed
+/// * `owner` - The account it was placed for, if any
+/// * `side` - Side of the market order it becomes
+/// * `stop_price` - Last trade price that triggers it
+/// * `quantity` - Size of the market order it becomes
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct StopOrder {
+    pub id: OrderId,
+    pub owner: Option<OwnerId>,
+    pub side: OrderSide,
+    pub stop_price: Decimal,
+    pub quantity: Decimal,
+}
+
+impl StopOrder {
+    fn is_triggered(&self, last_price: Decimal) -> bool {
+        match self.side {
+            OrderSide::Buy => last_price >= self.stop_price,
+            OrderSide::Sell => last_price <= self.stop_price,
+        }
+    }
+}
+
+impl OrderBook {
+    /// Place a stop order that becomes a market order once the last trade
+    /// price reaches `stop_price`. Returns its id.
+    ///
+    /// Stops wait outside the book and add no depth. They trigger in
+    /// arrival order after each order or uncross that trades, as long as
+    /// the phase accepts market orders, and each triggered stop emits a
+    /// [`BookEvent::StopTriggered`] with the result of its market order.
+    /// Trades of a triggered stop can trigger further stops. A stop whose
+    /// price the last trade already reached triggers straight away.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{BookEvent, OrderBook, OrderSide};
+    /// let mut book = OrderBook::new(dec!(0.01)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(5)).unwrap();
+    /// book.add_limit_order(OrderSide::Sell, dec!(101.00), dec!(5)).unwrap();
+    ///
+    /// // Buy 3 once the market trades at 100.00 or above
+    /// let stop = book.add_stop_order(OrderSide::Buy, dec!(100.00), dec!(3)).unwrap();
+    /// book.execute_market_order(OrderSide::Buy, dec!(4)).unwrap();
+    ///
+    /// assert_eq!(book.ask_levels(1), vec![(dec!(101.00), dec!(3))]);
+    /// assert!(matches!(
+    ///     book.drain_events().as_slice(),
+    ///     [BookEvent::StopTriggered { order_id, result: Ok(_) }] if *order_id == stop
+    /// ));
+    /// ```
+    pub fn add_stop_order(
+        &mut self,
+        side: OrderSide,
+        stop_price: Decimal,
+        quantity: Decimal,
+    ) -> Result<OrderId, BookError> {
+        self.submit_stop_order(None, side, stop_price, quantity)
+    }
+
+    /// Place a stop order on behalf of `owner`.
+    ///
+    /// The account's limits are checked with the notional at the stop
+    /// price, and again on the market order when it triggers.
+    pub fn add_stop_order_for(
+        &mut self,
+        owner: OwnerId,
+        side: OrderSide,
+        stop_price: Decimal,
+        quantity: Decimal,
+    ) -> Result<OrderId, BookError> {
+        self.submit_stop_order(Some(owner), side, stop_price, quantity)
+    }
+
+    fn submit_stop_order(
+        &mut self,
+        owner: Option<OwnerId>,
+        side: OrderSide,
+        stop_price: Decimal,
+        quantity: Decimal,
+    ) -> Result<OrderId, BookError> {
+        self.require_phase(TradingPhase::accepts_limit_orders)?;
+
+        if stop_price <= Decimal::ZERO {
+            return Err(BookError::InvalidPrice);
+        }
+
+        if quantity <= Decimal::ZERO {
+            return Err(BookError::InvalidQuantity);
+        }
+
+        if !self.spec.tick_table.is_aligned(stop_price) {
+            return Err(BookError::PriceNotAligned);
+        }
+
+        self.spec.check_quantity(quantity)?;
+
+        if let Some(owner) = owner {
+            self.check_risk(owner, side, quantity, stop_price * quantity, None, false)?;
+        }
+
+        let id = self.next_order_id();
+        self.stop_orders.push(StopOrder {
+            id,
+            owner,
+            side,
+            stop_price,
+            quantity,
+        });
+        self.trigger_stops();
+        Ok(id)
+    }
+
+    /// Cancel a stop order that has not triggered yet
+    pub fn cancel_stop_order(&mut self, order_id: OrderId) -> Result<StopOrder, BookError> {
+        let position = self
+            .stop_orders
+            .iter()
+            .position(|stop| stop.id == order_id)
+            .ok_or(BookError::OrderNotFound(order_id))?;
+        Ok(self.stop_orders.remove(position))
+    }
+
+    /// Stop orders waiting to trigger, in arrival order
+    pub fn stop_orders(&self) -> &[StopOrder] {
+        &self.stop_orders
+    }
+
+    // Execute every stop the last trade price has reached, oldest first,
+    // until none is left to trigger
+    pub(crate) fn trigger_stops(&mut self) {
+        while self.phase.accepts_market_orders() {
+            let Some(last_price) = self.reference_price else {
+                return;
+            };
+            let Some(position) = self
+                .stop_orders
+                .iter()
+                .position(|stop| stop.is_triggered(last_price))
+            else {
+                return;
+            };
+
+            let stop = self.stop_orders.remove(position);
+            let options = MarketOrderOptions {
+                allow_partial: true,
+                max_slippage: None,
+            };
+            let result = self.match_market_order(stop.owner, stop.side, stop.quantity, options);
+            self.events.push(BookEvent::StopTriggered {
+                order_id: stop.id,
+                result,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order::OrderStatus;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_stops_trigger_in_cascade() {
+        let mut book = OrderBook::new(dec!(0.01)).expect("tick spacing must be positive");
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(2))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(99.00), dec!(2))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(98.00), dec!(2))
+            .expect("invalid order");
+
+        // The second stop only triggers on the first one's trade at 99.00
+        let first = book
+            .add_stop_order(OrderSide::Sell, dec!(100.00), dec!(2))
+            .expect("valid stop");
+        let second = book
+            .add_stop_order(OrderSide::Sell, dec!(99.00), dec!(5))
+            .expect("valid stop");
+        let untouched = book
+            .add_stop_order(OrderSide::Sell, dec!(90.00), dec!(1))
+            .expect("valid stop");
+        assert_eq!(book.stop_orders().len(), 3);
+
+        book.execute_market_order(OrderSide::Sell, dec!(1))
+            .expect("enough liquidity");
+        let events = book.drain_events();
+        assert_eq!(events.len(), 2);
+        let BookEvent::StopTriggered {
+            order_id,
+            result: Ok(report),
+        } = &events[0]
+        else {
+            panic!("first stop did not trigger: {events:?}");
+        };
+        assert_eq!(*order_id, first);
+        assert_eq!(report.filled_quantity, dec!(2));
+
+        // Only 3 were left, the rest is cancelled
+        let BookEvent::StopTriggered {
+            order_id,
+            result: Ok(report),
+        } = &events[1]
+        else {
+            panic!("second stop did not trigger: {events:?}");
+        };
+        assert_eq!(*order_id, second);
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.filled_quantity, dec!(3));
+
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.stop_orders().len(), 1);
+        assert_eq!(book.stop_orders()[0].id, untouched);
+        assert_eq!(
+            book.cancel_stop_order(untouched).map(|stop| stop.quantity),
+            Ok(dec!(1))
+        );
+        assert_eq!(
+            book.cancel_stop_order(untouched),
+            Err(BookError::OrderNotFound(untouched))
+        );
+        assert_eq!(
+            book.add_stop_order(OrderSide::Buy, dec!(100.001), dec!(1)),
+            Err(BookError::PriceNotAligned)
+        );
+    }
+}
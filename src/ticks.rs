@@ -1,4 +1,149 @@
+use crate::error::BookError;
+use crate::order::OrderSide;
+
 use rust_decimal::Decimal;
+use std::cmp::Ordering;
+
+/// How a book treats order prices that do not sit on a tick.
+///
+/// Whichever policy is used, an order matches and rests at the same
+/// normalized price.
+///
+/// # Example
+/// With tick_size 0.01 and an order price of 100.017:
+/// - `Nearest`: 100.02 for both sides
+/// - `Reject`: the order is rejected with `BookError::PriceNotAligned`
+/// - `Conservative`: 100.01 for a buy, 100.02 for a sell
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum TickPolicy {
+    /// Round to the nearest tick (the behavior of `Tick::new`)
+    #[default]
+    Nearest,
+    /// Reject prices that are not a multiple of the tick size
+    Reject,
+    /// Round buys down and sells up, so an order is never more aggressive
+    /// than its requested price
+    Conservative,
+}
+
+/// What happens to resting orders whose price is not on a tick after a
+/// live book changes tick size (see `OrderBook::change_tick_size`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TickMigration {
+    /// Refuse the change if any resting order would be misaligned
+    Reject,
+    /// Cancel misaligned orders
+    Cancel,
+    /// Move misaligned orders to the nearest less aggressive tick, buys
+    /// down and sells up, so the book can never become crossed
+    Round,
+}
+
+/// Price-dependent tick sizes, as used by equity and some crypto venues.
+///
+/// The table is a list of bands `(lower_bound, tick_size)`, lowest first.
+/// A price uses the tick size of the highest band whose lower bound is at
+/// or below it, so a boundary price belongs to the band above. The first
+/// band must start at zero, and every boundary must be a multiple of the
+/// tick sizes on both sides of it, so rounding inside a band can land on
+/// the boundary but never past it.
+///
+/// # Example
+/// ```
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::TickTable;
+/// // 0.0001 below 1.00, 0.01 from 1.00
+/// let table = TickTable::new(vec![(dec!(0), dec!(0.0001)), (dec!(1.00), dec!(0.01))]).unwrap();
+///
+/// assert_eq!(table.tick_size_at(dec!(0.5)), dec!(0.0001));
+/// assert_eq!(table.tick_size_at(dec!(1.00)), dec!(0.01));
+///
+/// // One tick either side of the boundary
+/// assert_eq!(table.tick_up(dec!(0.9999)), dec!(1.0000));
+/// assert_eq!(table.tick_up(dec!(1.00)), dec!(1.01));
+/// assert_eq!(table.tick_down(dec!(1.00)), Some(dec!(0.9999)));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TickTable {
+    bands: Vec<(Decimal, Decimal)>, // (lower bound, tick size), lowest first
+}
+
+impl TickTable {
+    /// A single tick size for every price
+    pub fn fixed(tick_size: Decimal) -> Self {
+        Self {
+            bands: vec![(Decimal::ZERO, tick_size)],
+        }
+    }
+
+    pub fn new(bands: Vec<(Decimal, Decimal)>) -> Result<Self, BookError> {
+        let table = Self { bands };
+        table.validate()?;
+        Ok(table)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), BookError> {
+        if self.bands.first().map(|(lower, _)| *lower) != Some(Decimal::ZERO) {
+            return Err(BookError::InvalidTickTable);
+        }
+
+        if self
+            .bands
+            .iter()
+            .any(|(_, tick_size)| *tick_size <= Decimal::ZERO)
+        {
+            return Err(BookError::InvalidTickSize);
+        }
+
+        for pair in self.bands.windows(2) {
+            let (_, below_tick) = pair[0];
+            let (boundary, above_tick) = pair[1];
+            if boundary <= pair[0].0
+                || !(boundary % below_tick).is_zero()
+                || !(boundary % above_tick).is_zero()
+            {
+                return Err(BookError::InvalidTickTable);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tick size that applies at `price`
+    pub fn tick_size_at(&self, price: Decimal) -> Decimal {
+        self.band(|lower| lower <= price)
+    }
+
+    /// Whether `price` sits on a tick of its band
+    pub fn is_aligned(&self, price: Decimal) -> bool {
+        (price % self.tick_size_at(price)).is_zero()
+    }
+
+    /// The lowest valid price strictly above `price`
+    pub fn tick_up(&self, price: Decimal) -> Decimal {
+        let tick_size = self.tick_size_at(price);
+        (price / tick_size).floor() * tick_size + tick_size
+    }
+
+    /// The highest valid price strictly below `price`, None if that
+    /// would not be positive
+    pub fn tick_down(&self, price: Decimal) -> Option<Decimal> {
+        // Below a boundary the lower band's tick applies
+        let tick_size = self.band(|lower| lower < price);
+        let level = (price / tick_size).ceil() * tick_size - tick_size;
+        (level > Decimal::ZERO).then_some(level)
+    }
+
+    // Tick size of the highest band whose lower bound passes `contains`
+    fn band(&self, contains: impl Fn(Decimal) -> bool) -> Decimal {
+        self.bands
+            .iter()
+            .rev()
+            .find(|(lower, _)| contains(*lower))
+            .unwrap_or(&self.bands[0])
+            .1
+    }
+}
 
 /// A price level in the order book that orders can rest at.
 ///
@@ -13,7 +158,8 @@ use rust_decimal::Decimal;
 ///
 /// # Ordering
 /// Implements total ordering for use in BTreeMap:
-/// - Ordered by price level for efficient best bid/ask lookup
+/// - Ordered and compared by price level alone, so a price reached from
+///   either side of a tick table band boundary is the same level
 /// - Enables price-time priority matching
 ///
 /// # Example
@@ -23,20 +169,40 @@ use rust_decimal::Decimal;
 /// let tick = Tick::new(dec!(100.012), dec!(0.01)).unwrap();
 /// assert_eq!(tick.level(), dec!(100.01));  // Normalized to tick
 /// ```
-#[derive(Eq, PartialEq, Ord, PartialOrd, Clone)]
+#[derive(Clone)]
 pub struct Tick {
     level: Decimal,     // The normalized price level
     tick_size: Decimal, // Minimum price increment
 }
 
+impl PartialEq for Tick {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level
+    }
+}
+
+impl Eq for Tick {}
+
+impl PartialOrd for Tick {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tick {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.level.cmp(&other.level)
+    }
+}
+
 impl Tick {
-    pub fn new(price: Decimal, tick_size: Decimal) -> eyre::Result<Self> {
+    pub fn new(price: Decimal, tick_size: Decimal) -> Result<Self, BookError> {
         if price <= Decimal::ZERO {
-            return Err(eyre::eyre!("Price must be positive"));
+            return Err(BookError::InvalidPrice);
         }
 
         if tick_size <= Decimal::ZERO {
-            return Err(eyre::eyre!("Tick size must be positive"));
+            return Err(BookError::InvalidTickSize);
         }
 
         let normalized = Self::normalize(price, tick_size);
@@ -46,6 +212,60 @@ impl Tick {
         })
     }
 
+    /// Create a tick rounded to the nearest tick of the band `price` is in.
+    pub fn in_table(price: Decimal, table: &TickTable) -> Result<Self, BookError> {
+        let tick = Self::new(price, table.tick_size_at(price))?;
+        Ok(Self::on_level(tick.level, table))
+    }
+
+    /// Create the tick an order on `side` at `price` belongs to under `policy`.
+    ///
+    /// # Example
+    /// ```
+    /// # use rust_decimal_macros::dec;
+    /// # use limitbook::{OrderSide, Tick, TickPolicy, TickTable};
+    /// let table = TickTable::fixed(dec!(0.01));
+    /// let tick = Tick::for_order(dec!(100.017), &table, OrderSide::Buy, TickPolicy::Conservative).unwrap();
+    /// assert_eq!(tick.level(), dec!(100.01));
+    ///
+    /// assert!(Tick::for_order(dec!(100.017), &table, OrderSide::Buy, TickPolicy::Reject).is_err());
+    /// ```
+    pub fn for_order(
+        price: Decimal,
+        table: &TickTable,
+        side: OrderSide,
+        policy: TickPolicy,
+    ) -> Result<Self, BookError> {
+        let tick_size = table.tick_size_at(price);
+        let tick = Self::new(price, tick_size)?;
+        if tick.level == price {
+            return Ok(tick);
+        }
+
+        let level = match (policy, side) {
+            (TickPolicy::Nearest, _) => tick.level,
+            (TickPolicy::Reject, _) => return Err(BookError::PriceNotAligned),
+            (TickPolicy::Conservative, OrderSide::Buy) => (price / tick_size).floor() * tick_size,
+            (TickPolicy::Conservative, OrderSide::Sell) => (price / tick_size).ceil() * tick_size,
+        };
+
+        // A buy below one tick has no valid price to round down to
+        if level <= Decimal::ZERO {
+            return Err(BookError::InvalidPrice);
+        }
+
+        Ok(Self::on_level(level, table))
+    }
+
+    // Rounding may land on a band's lower boundary, which takes the tick
+    // size of that band rather than the one below it
+    fn on_level(level: Decimal, table: &TickTable) -> Self {
+        Self {
+            level,
+            tick_size: table.tick_size_at(level),
+        }
+    }
+
     // Static method to handle normalization
     fn normalize(price: Decimal, tick_size: Decimal) -> Decimal {
         (price / tick_size).round() * tick_size
@@ -54,6 +274,11 @@ impl Tick {
     pub fn level(&self) -> Decimal {
         self.level
     }
+
+    /// The tick size of the band this level is in
+    pub fn tick_size(&self) -> Decimal {
+        self.tick_size
+    }
 }
 
 #[cfg(test)]
@@ -87,4 +312,101 @@ mod tests {
             dec!(10.02)
         );
     }
+
+    #[test]
+    fn test_tick_policies() {
+        let table = TickTable::fixed(dec!(0.01));
+        let level = |side, policy| {
+            Tick::for_order(dec!(10.013), &table, side, policy).map(|tick| tick.level())
+        };
+
+        assert_eq!(level(OrderSide::Buy, TickPolicy::Nearest), Ok(dec!(10.01)));
+        assert_eq!(level(OrderSide::Sell, TickPolicy::Nearest), Ok(dec!(10.01)));
+        assert_eq!(
+            level(OrderSide::Buy, TickPolicy::Conservative),
+            Ok(dec!(10.01))
+        );
+        assert_eq!(
+            level(OrderSide::Sell, TickPolicy::Conservative),
+            Ok(dec!(10.02))
+        );
+        assert_eq!(
+            level(OrderSide::Buy, TickPolicy::Reject),
+            Err(BookError::PriceNotAligned)
+        );
+
+        // Aligned prices pass every policy unchanged
+        for policy in [
+            TickPolicy::Nearest,
+            TickPolicy::Reject,
+            TickPolicy::Conservative,
+        ] {
+            let tick = Tick::for_order(dec!(10.02), &table, OrderSide::Sell, policy);
+            assert_eq!(tick.map(|tick| tick.level()), Ok(dec!(10.02)));
+        }
+
+        // Nothing to round a sub-tick buy down to
+        assert_eq!(
+            Tick::for_order(
+                dec!(0.004),
+                &table,
+                OrderSide::Buy,
+                TickPolicy::Conservative
+            )
+            .map(|tick| tick.level()),
+            Err(BookError::InvalidPrice)
+        );
+    }
+
+    #[test]
+    fn test_tick_table_bands() {
+        let table = TickTable::new(vec![
+            (dec!(0), dec!(0.0001)),
+            (dec!(1), dec!(0.01)),
+            (dec!(100), dec!(0.05)),
+        ])
+        .expect("valid table");
+
+        // Normalization uses the band the price falls in
+        let nearest = |price| Tick::in_table(price, &table).map(|tick| tick.level());
+        assert_eq!(nearest(dec!(0.12344)), Ok(dec!(0.1234)));
+        assert_eq!(nearest(dec!(5.123)), Ok(dec!(5.12)));
+        assert_eq!(nearest(dec!(100.07)), Ok(dec!(100.05)));
+        // Rounding may land on, never past, a boundary, and then takes the
+        // tick of the band above. It is the same level as 1.00.
+        let boundary = Tick::in_table(dec!(0.99996), &table).expect("valid price");
+        assert_eq!(boundary.level(), dec!(1.0000));
+        assert_eq!(boundary.tick_size(), dec!(0.01));
+        assert!(boundary == Tick::in_table(dec!(1.00), &table).expect("valid price"));
+
+        // Conservative rounding near a boundary stays inside the band
+        let conservative = |price, side| {
+            Tick::for_order(price, &table, side, TickPolicy::Conservative).map(|tick| tick.level())
+        };
+        assert_eq!(conservative(dec!(1.004), OrderSide::Buy), Ok(dec!(1.00)));
+        assert_eq!(
+            conservative(dec!(99.996), OrderSide::Sell),
+            Ok(dec!(100.00))
+        );
+
+        assert!(table.is_aligned(dec!(0.9999)));
+        assert!(!table.is_aligned(dec!(1.0001)));
+
+        // Stepping across each boundary uses the tick on the far side
+        assert_eq!(table.tick_up(dec!(99.99)), dec!(100.00));
+        assert_eq!(table.tick_up(dec!(100.00)), dec!(100.05));
+        assert_eq!(table.tick_down(dec!(100.00)), Some(dec!(99.99)));
+        assert_eq!(table.tick_down(dec!(1.00)), Some(dec!(0.9999)));
+        assert_eq!(table.tick_down(dec!(0.0001)), None);
+
+        // Boundaries must sit on the ticks of both neighbouring bands
+        assert_eq!(
+            TickTable::new(vec![(dec!(0), dec!(0.01)), (dec!(1.005), dec!(0.05))]),
+            Err(BookError::InvalidTickTable)
+        );
+        assert_eq!(
+            TickTable::new(vec![(dec!(1), dec!(0.01))]),
+            Err(BookError::InvalidTickTable)
+        );
+    }
 }
@@ -0,0 +1,308 @@
+use crate::auction::UncrossResult;
+use crate::error::BookError;
+use crate::event::BookEvent;
+use crate::order::{Fill, OrderSide};
+use crate::order_book::OrderBook;
+use crate::phase::TradingPhase;
+
+use rust_decimal::Decimal;
+use std::time::Duration;
+
+/// Volatility interruption: when continuous trading would move the price
+/// more than `max_move` within `window`, the book switches to a volatility
+/// auction for `auction_duration` instead.
+///
+/// `max_move` is a fraction of the earlier price, so 0.02 allows a trade at
+/// 102.00 but not above within a window that traded at 100.00. The book
+/// keeps the price of every fill with the time from its clock, and before
+/// each order matches works out how far prices may move on its side given
+/// the trades still in the window. Matching stops at that limit, so the
+/// interrupting trade never happens: the order's remainder rests in the
+/// auction (limit orders) or is cancelled (market orders). A market order
+/// only starts the auction if it traded up to the limit: one that is
+/// rejected or fills nothing leaves nothing behind to auction.
+///
+/// The auction reopens continuous trading through `uncross` once
+/// `auction_duration` has passed, the next time `poll` is called.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use rust_decimal_macros::dec;
+/// # use limitbook::{ManualClock, OrderBook, OrderSide, TradingPhase, VolatilityBreaker};
+/// let clock = ManualClock::new();
+/// let breaker = VolatilityBreaker::new(
+///     dec!(0.02),
+///     Duration::from_secs(60),
+///     Duration::from_secs(120),
+/// )
+/// .unwrap();
+/// let mut book = OrderBook::new(dec!(0.01))
+///     .unwrap()
+///     .with_clock(clock.clone())
+///     .with_volatility_breaker(breaker);
+///
+/// book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(1)).unwrap();
+/// book.add_limit_order(OrderSide::Sell, dec!(103.00), dec!(1)).unwrap();
+/// book.execute_market_order(OrderSide::Buy, dec!(1)).unwrap();
+///
+/// // 103.00 is more than 2% above the trade at 100.00
+/// let report = book.add_limit_order(OrderSide::Buy, dec!(103.00), dec!(1)).unwrap();
+/// assert!(report.fills.is_empty());
+/// assert_eq!(book.phase(), TradingPhase::Auction);
+///
+/// clock.advance(Duration::from_secs(120));
+/// let result = book.poll().unwrap();
+/// assert_eq!(result.fills[0].price, dec!(103.00));
+/// assert_eq!(book.phase(), TradingPhase::Continuous);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct VolatilityBreaker {
+    max_move: Decimal,
+    window: Duration,
+    auction_duration: Duration,
+}
+
+impl VolatilityBreaker {
+    pub fn new(
+        max_move: Decimal,
+        window: Duration,
+        auction_duration: Duration,
+    ) -> Result<Self, BookError> {
+        if max_move <= Decimal::ZERO || max_move > Decimal::ONE {
+            return Err(BookError::InvalidPercentage);
+        }
+
+        if window.is_zero() || auction_duration.is_zero() {
+            return Err(BookError::InvalidInterval);
+        }
+
+        Ok(Self {
+            max_move,
+            window,
+            auction_duration,
+        })
+    }
+
+    pub fn max_move(&self) -> Decimal {
+        self.max_move
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn auction_duration(&self) -> Duration {
+        self.auction_duration
+    }
+}
+
+impl OrderBook {
+    /// Interrupt continuous trading on large price moves, see
+    /// [`VolatilityBreaker`]
+    pub fn with_volatility_breaker(mut self, breaker: VolatilityBreaker) -> Self {
+        self.volatility_breaker = Some(breaker);
+        self
+    }
+
+    pub fn volatility_breaker(&self) -> Option<VolatilityBreaker> {
+        self.volatility_breaker
+    }
+
+    /// When the running volatility auction reopens, None if there is none
+    pub fn volatility_auction_ends_at(&self) -> Option<Duration> {
+        self.volatility_auction_ends_at
+    }
+
+    // Furthest price an order on `side` may trade at without moving more
+    // than the breaker allows from any trade in the window
+    pub(crate) fn volatility_limit(&self, side: OrderSide) -> Option<Decimal> {
+        let breaker = self.volatility_breaker?;
+        if !self.phase.matches_on_arrival() {
+            return None;
+        }
+
+        let window_start = self.clock.now().saturating_sub(breaker.window);
+        let prices = self
+            .trade_history
+            .iter()
+            .filter(|(time, _)| *time >= window_start)
+            .map(|(_, price)| *price);
+
+        match side {
+            OrderSide::Buy => prices
+                .min()
+                .map(|low| low * (Decimal::ONE + breaker.max_move)),
+            OrderSide::Sell => prices
+                .max()
+                .map(|high| high * (Decimal::ONE - breaker.max_move)),
+        }
+    }
+
+    // Remember trade prices for the breaker, dropping trades that left the
+    // window
+    pub(crate) fn record_trades(&mut self, fills: &[Fill]) {
+        let Some(breaker) = self.volatility_breaker else {
+            return;
+        };
+
+        let now = self.clock.now();
+        let window_start = now.saturating_sub(breaker.window);
+        while self
+            .trade_history
+            .front()
+            .is_some_and(|(time, _)| *time < window_start)
+        {
+            self.trade_history.pop_front();
+        }
+
+        self.trade_history
+            .extend(fills.iter().map(|fill| (now, fill.price)));
+    }
+
+    // An order on `side` would have traded beyond `limit`: switch to a
+    // volatility auction
+    pub(crate) fn start_volatility_auction(&mut self, side: OrderSide, limit: Decimal) {
+        let Some(breaker) = self.volatility_breaker else {
+            return;
+        };
+        if !self.phase.can_transition_to(TradingPhase::Auction) {
+            return;
+        }
+
+        let ends_at = self.clock.now() + breaker.auction_duration;
+        self.events.push(BookEvent::VolatilityInterruption {
+            side,
+            limit,
+            ends_at,
+        });
+        self.set_phase(TradingPhase::Auction);
+        self.volatility_auction_ends_at = Some(ends_at);
+    }
+
+    // Reopen continuous trading once the volatility auction has run its
+    // course
+    pub(crate) fn poll_volatility_auction(&mut self) -> Option<UncrossResult> {
+        let ends_at = self.volatility_auction_ends_at?;
+        if self.clock.now() < ends_at {
+            return None;
+        }
+
+        self.uncross().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::order::{MarketOrderOptions, OrderStatus};
+    use rust_decimal_macros::dec;
+
+    fn breaker_book(clock: &ManualClock) -> OrderBook {
+        let breaker =
+            VolatilityBreaker::new(dec!(0.05), Duration::from_secs(60), Duration::from_secs(30))
+                .expect("valid breaker");
+        OrderBook::new(dec!(0.01))
+            .expect("tick spacing must be positive")
+            .with_clock(clock.clone())
+            .with_volatility_breaker(breaker)
+    }
+
+    #[test]
+    fn test_breaker_validation() {
+        let window = Duration::from_secs(60);
+        assert_eq!(
+            VolatilityBreaker::new(dec!(0), window, window),
+            Err(BookError::InvalidPercentage)
+        );
+        assert_eq!(
+            VolatilityBreaker::new(dec!(0.05), Duration::ZERO, window),
+            Err(BookError::InvalidInterval)
+        );
+    }
+
+    #[test]
+    fn test_market_sweep_triggers_volatility_auction() {
+        let clock = ManualClock::new();
+        let mut book = breaker_book(&clock);
+        book.add_limit_order(OrderSide::Buy, dec!(100.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(96.00), dec!(5))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Buy, dec!(90.00), dec!(5))
+            .expect("invalid order");
+        book.execute_market_order(OrderSide::Sell, dec!(1))
+            .expect("no history yet");
+
+        // Sells may go down to 95.00, the 90.00 bid is out of reach
+        let options = MarketOrderOptions {
+            allow_partial: true,
+            max_slippage: None,
+        };
+        let report = book
+            .execute_market_order_with(OrderSide::Sell, dec!(14), options)
+            .expect("partial fills allowed");
+        assert_eq!(report.status, OrderStatus::Cancelled);
+        assert_eq!(report.filled_quantity, dec!(9));
+        assert_eq!(
+            report.reject_reason,
+            Some(BookError::VolatilityInterruption)
+        );
+
+        assert_eq!(book.phase(), TradingPhase::Auction);
+        assert_eq!(
+            book.volatility_auction_ends_at(),
+            Some(Duration::from_secs(30))
+        );
+        let events = book.drain_events();
+        assert_eq!(
+            events[0],
+            BookEvent::VolatilityInterruption {
+                side: OrderSide::Sell,
+                limit: dec!(95.00),
+                ends_at: Duration::from_secs(30),
+            }
+        );
+
+        // Orders collect for the auction, which reopens on time
+        book.add_limit_order(OrderSide::Sell, dec!(89.00), dec!(2))
+            .expect("auction accepts limit orders");
+        clock.advance(Duration::from_secs(29));
+        assert!(book.poll().is_none());
+        clock.advance(Duration::from_secs(1));
+        let result = book.poll().expect("auction is due");
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+        assert_eq!(book.volatility_auction_ends_at(), None);
+    }
+
+    #[test]
+    fn test_trades_leave_the_window() {
+        let clock = ManualClock::new();
+        let mut book = breaker_book(&clock);
+        book.add_limit_order(OrderSide::Sell, dec!(100.00), dec!(1))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(104.00), dec!(1))
+            .expect("invalid order");
+        book.add_limit_order(OrderSide::Sell, dec!(108.00), dec!(1))
+            .expect("invalid order");
+        book.execute_market_order(OrderSide::Buy, dec!(1))
+            .expect("no history yet");
+
+        clock.advance(Duration::from_secs(40));
+        book.execute_market_order(OrderSide::Buy, dec!(1))
+            .expect("within 5% of 100.00");
+
+        // 108.00 is within 5% of 104.00 once the 100.00 trade is older than
+        // the window
+        assert_eq!(
+            book.simulate_market_order(OrderSide::Buy, dec!(1)),
+            Err(BookError::VolatilityInterruption)
+        );
+        clock.advance(Duration::from_secs(21));
+        book.execute_market_order(OrderSide::Buy, dec!(1))
+            .expect("old trade left the window");
+        assert_eq!(book.phase(), TradingPhase::Continuous);
+    }
+}